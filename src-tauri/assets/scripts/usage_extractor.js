@@ -0,0 +1,313 @@
+// Copilot usage extractor, injected into the GitHub billing pages
+// extractor.rs renders `EXTRACTOR` above this script:
+//   version    extractor version, echoed back with every result
+//   mode       "auth": login window, hands the result over by redirecting to the success URL
+//              "hidden": hidden webview, reports through the hidden_webview_event command
+//   entity     billing entity kind: "personal", "organization" or "enterprise"; the page
+//              is the entity's billing page and the usage endpoints point at it
+//   periods    `period` values of the usage table, oldest first; the card uses the last one
+//   group      `group` value of the usage table
+//   maxPages   upper bound on usage table pages fetched per period
+//   endpoints  paths of the user and organizations APIs, enterprises page, usage card and
+//              usage table
+(function() {
+  const TAG = EXTRACTOR.mode === 'auth' ? '[AuthInjector]' : '[HiddenAuth]';
+  const log = (...args) => console.log(TAG, ...args);
+  const logError = (...args) => console.error(TAG, ...args);
+
+  log('Script loaded, extractor version', EXTRACTOR.version);
+
+  const BILLING_HEADERS = {
+    'Accept': 'application/json',
+    'x-requested-with': 'XMLHttpRequest'
+  };
+
+  async function getUserId() {
+    log('Attempting to get User ID via API...');
+    try {
+      const response = await fetch(EXTRACTOR.endpoints.user, {
+        headers: { 'Accept': 'application/json' }
+      });
+      if (!response.ok) {
+        logError('API request failed:', response.status);
+        return { success: false, error: 'API request failed: ' + response.status };
+      }
+      const data = await response.json();
+      log('User ID retrieved:', data.id);
+      return { success: true, id: data.id };
+    } catch (error) {
+      logError('API request error:', error);
+      return { success: false, error: error.message };
+    }
+  }
+
+  function getCustomerIdFromDOM() {
+    log('Attempting to get Customer ID from DOM...');
+    try {
+      const el = document.querySelector('script[data-target="react-app.embeddedData"]');
+      if (!el) {
+        return { success: false, error: 'Embedded data element not found' };
+      }
+      const data = JSON.parse(el.textContent);
+      const customerId = data?.payload?.customer?.customerId;
+      if (!customerId) {
+        return { success: false, error: 'Customer ID not found in embedded data' };
+      }
+      log('Customer ID found in DOM:', customerId);
+      return { success: true, id: customerId };
+    } catch (error) {
+      logError('DOM extraction error:', error);
+      return { success: false, error: error.message };
+    }
+  }
+
+  function getCustomerIdFromHTML() {
+    log('Attempting to get Customer ID from HTML regex...');
+    try {
+      const html = document.body.innerHTML;
+      const patterns = [
+        /customerId":(\d+)/,
+        /customerId&quot;:(\d+)/,
+        /customer_id=(\d+)/,
+        /"customerId":(\d+)/,
+        /data-customer-id="(\d+)"/
+      ];
+      for (const pattern of patterns) {
+        const match = html.match(pattern);
+        if (match && match[1]) {
+          log('Customer ID matched pattern:', pattern);
+          return { success: true, id: parseInt(match[1]) };
+        }
+      }
+      return { success: false, error: 'No customer ID pattern matched' };
+    } catch (error) {
+      logError('HTML extraction error:', error);
+      return { success: false, error: error.message };
+    }
+  }
+
+  async function extractCustomerId() {
+    // The user API only knows the personal account; other entities are read from their page
+    let result = EXTRACTOR.entity === 'personal'
+      ? await getUserId()
+      : { success: false, error: 'Not a personal account' };
+    if (!result.success) {
+      result = getCustomerIdFromDOM();
+    }
+    if (!result.success) {
+      result = getCustomerIdFromHTML();
+    }
+    return result;
+  }
+
+  // Organizations from the API, enterprises from the links on the enterprises page
+  async function listBillingEntities() {
+    try {
+      const res = await fetch(EXTRACTOR.endpoints.organizations, {
+        headers: { 'Accept': 'application/json' }
+      });
+      const orgs = res.ok ? await res.json() : [];
+      const organizations = (Array.isArray(orgs) ? orgs : []).map((org) => ({ login: org.login }));
+
+      const page = await fetch(EXTRACTOR.endpoints.enterprises);
+      const html = page.ok ? await page.text() : '';
+      const slugs = new Set();
+      for (const match of html.matchAll(/href="\/enterprises\/([\w-]+)"/g)) {
+        slugs.add(match[1]);
+      }
+      const enterprises = [...slugs].map((slug) => ({ slug }));
+
+      log('Billing entities:', organizations.length, 'organizations,', enterprises.length, 'enterprises');
+      return { success: true, organizations, enterprises };
+    } catch (error) {
+      logError('Billing entity listing error:', error);
+      return { success: false, error: error.message };
+    }
+  }
+
+  async function fetchUsageCard(customerId) {
+    try {
+      const period = EXTRACTOR.periods[EXTRACTOR.periods.length - 1];
+      const res = await fetch(`${EXTRACTOR.endpoints.usageCard}?customer_id=${customerId}&period=${period}`, {
+        headers: BILLING_HEADERS
+      });
+      log('Usage card response status:', res.status);
+      if (!res.ok) {
+        return { success: false, error: 'Usage card request failed: ' + res.status };
+      }
+      const data = await res.json();
+      return { success: true, data };
+    } catch (error) {
+      logError('Usage card fetch error:', error);
+      return { success: false, error: error.message };
+    }
+  }
+
+  // Identifies a usage table row across pages (days have an id, model rows may not)
+  function usageRowKey(row) {
+    return row.id || JSON.stringify(row.cells && row.cells[0]);
+  }
+
+  // Whether the usage table reports or implies another page after `page`
+  function hasNextUsagePage(data, page, rowCount, newRows) {
+    const table = (data && data.table) || {};
+    const meta = table.pagination || (data && data.pagination) || {};
+    const totalPages = meta.totalPages ?? meta.total_pages ?? table.totalPages ?? data?.totalPages;
+    if (typeof totalPages === 'number') return page < totalPages;
+    const total = meta.totalCount ?? meta.total_count ?? table.totalCount ?? data?.totalCount;
+    if (typeof total === 'number') return rowCount < total;
+    const hasNext = meta.hasNextPage ?? meta.has_next_page ?? table.hasNextPage ?? data?.hasNextPage;
+    if (typeof hasNext === 'boolean') return hasNext;
+    // No pagination metadata: keep going while pages bring new rows
+    return newRows > 0;
+  }
+
+  async function fetchUsageTable(customerId) {
+    try {
+      const pages = [];
+      for (const period of EXTRACTOR.periods) {
+        const seen = new Set();
+        for (let page = 1; page <= EXTRACTOR.maxPages; page++) {
+          const res = await fetch(`${EXTRACTOR.endpoints.usageTable}?customer_id=${customerId}&group=${EXTRACTOR.group}&period=${period}&query=&page=${page}`, {
+            headers: BILLING_HEADERS
+          });
+          log('Usage table period', period, 'page', page, 'response status:', res.status);
+          if (!res.ok) {
+            if (pages.length > 0) break;
+            return { success: false, error: 'Usage table request failed: ' + res.status };
+          }
+          const data = await res.json();
+          const keys = (data?.table?.rows || []).map(usageRowKey);
+          const newRows = keys.filter((key) => !seen.has(key)).length;
+          keys.forEach((key) => seen.add(key));
+          pages.push(data);
+          log('Usage table page', page, 'rows:', keys.length, 'new:', newRows);
+          if (!hasNextUsagePage(data, page, seen.size, newRows)) break;
+        }
+      }
+      return { success: true, data: pages[0], pages };
+    } catch (error) {
+      logError('Usage table fetch error:', error);
+      return { success: false, error: error.message };
+    }
+  }
+
+  // Login window: send the user to the billing page, then hand the result over by redirect
+  function runInAuthWindow() {
+    const isHomepage = (url) => url === 'https://github.com/' || url === 'https://github.com';
+    let currentUrl = location.href;
+
+    async function extractAndSend() {
+      const result = await extractCustomerId();
+      if (!(result.success && result.id)) {
+        logError('Failed to extract customer ID:', result.error);
+        if (window.__TAURI__?.event) {
+          window.__TAURI__.event.emit('auth:extraction-failed', {
+            error: result.error || 'Unknown extraction error',
+            extractorVersion: EXTRACTOR.version
+          });
+        }
+        return;
+      }
+
+      log('Extraction success, ID:', result.id, 'fetching usage data...');
+      const payload = {
+        id: result.id,
+        extractorVersion: EXTRACTOR.version,
+        usageCard: await fetchUsageCard(result.id),
+        usageTable: await fetchUsageTable(result.id)
+      };
+      const hash = encodeURIComponent(JSON.stringify(payload));
+      window.location.href = 'https://copilot-auth-success.local/success#payload=' + hash;
+    }
+
+    function checkUrl() {
+      const newUrl = location.href;
+      if (isHomepage(newUrl)) {
+        log('Detected homepage, redirecting to billing...');
+        window.location.href = 'https://github.com/settings/billing';
+      }
+      if (newUrl !== currentUrl) {
+        currentUrl = newUrl;
+        log('URL changed to:', currentUrl);
+        if (currentUrl.includes('/settings/billing')) {
+          log('Billing page detected, starting extraction in 1.5s');
+          setTimeout(extractAndSend, 1500);
+        }
+      }
+    }
+
+    new MutationObserver(checkUrl).observe(document, { subtree: true, childList: true });
+    window.addEventListener('popstate', checkUrl);
+    window.addEventListener('hashchange', checkUrl);
+
+    if (isHomepage(location.href)) {
+      log('Detected homepage, redirecting to billing...');
+      window.location.href = 'https://github.com/settings/billing';
+    }
+    if (location.href.includes('/settings/billing')) {
+      log('Already on billing page, starting extraction in 1.5s');
+      setTimeout(extractAndSend, 1500);
+    }
+  }
+
+  // Hidden webview: report each step as a hidden_webview_event
+  function runInHiddenWebview() {
+    async function sendResult(kind, payload) {
+      const body = JSON.stringify({ ...payload, extractorVersion: EXTRACTOR.version });
+      try {
+        if (window.__TAURI__ && window.__TAURI__.core) {
+          await window.__TAURI__.core.invoke('hidden_webview_event', { event: kind, payload: body });
+          log('Sent event:', kind);
+        } else {
+          logError('Tauri not available');
+          // Fallback: store in localStorage for parent window to pick up
+          localStorage.setItem('tauri_hidden_webview_' + kind, body);
+        }
+      } catch (e) {
+        logError('Failed to send:', e);
+      }
+    }
+
+    async function runExtraction() {
+      // An expired session lands on the sign-in or two-factor page instead of billing
+      await sendResult('auth:extraction:page', { url: location.href });
+
+      log('Starting extraction...');
+      const customerResult = await extractCustomerId();
+      await sendResult('auth:extraction:customer', customerResult);
+
+      if (!customerResult.success) {
+        await sendResult('auth:extraction:complete', { success: false });
+        return;
+      }
+
+      const usageCard = await fetchUsageCard(customerResult.id);
+      const usageTable = await fetchUsageTable(customerResult.id);
+      await sendResult('auth:extraction:usage', {
+        customerId: customerResult.id,
+        usageCard,
+        usageTable
+      });
+
+      if (EXTRACTOR.entity === 'personal') {
+        await sendResult('auth:extraction:entities', await listBillingEntities());
+      }
+
+      await sendResult('auth:extraction:complete', { success: true });
+      log('Extraction complete');
+    }
+
+    if (document.readyState === 'complete') {
+      setTimeout(runExtraction, 1500);
+    } else {
+      window.addEventListener('load', () => setTimeout(runExtraction, 1500));
+    }
+  }
+
+  if (EXTRACTOR.mode === 'auth') {
+    runInAuthWindow();
+  } else {
+    runInHiddenWebview();
+  }
+})();
@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+use crate::store::StoreManager;
+
+/// Account kept in the app data directory itself, by the managed `StoreManager`
+pub const DEFAULT_ACCOUNT_ID: &str = "default";
+
+const ACCOUNTS_DIRNAME: &str = "accounts";
+const WEBVIEW_DIRNAME: &str = "webview";
+
+/// Error for accounts other than the default one where `supports_isolated_webviews` is false
+pub const ISOLATION_UNSUPPORTED: &str =
+    "More than one GitHub account needs macOS 14 or later, where each account gets its own sign-in";
+
+/// A named GitHub account tracked by the app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProfile {
+    pub id: String,
+    pub name: String,
+}
+
+impl AccountProfile {
+    pub fn default_account() -> Self {
+        Self {
+            id: DEFAULT_ACCOUNT_ID.to_string(),
+            name: "Default".to_string(),
+        }
+    }
+}
+
+/// Latest usage of one account, for the tray
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountUsage {
+    pub id: String,
+    pub name: String,
+    pub is_authenticated: bool,
+    pub used: u32,
+    pub limit: u32,
+}
+
+/// Id for a new account named `name`: a slug of the name, made unique among `existing`
+pub fn new_account_id(name: &str, existing: &[AccountProfile]) -> String {
+    let mut slug = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    let slug = match slug.trim_end_matches('-') {
+        "" => "account".to_string(),
+        slug => slug.to_string(),
+    };
+
+    let taken = |id: &str| existing.iter().any(|a| a.id == id);
+    let mut id = slug.clone();
+    let mut suffix = 2;
+    while taken(&id) {
+        id = format!("{}-{}", slug, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+/// Where the webviews of an account keep cookies and site data
+/// The default account keeps the default webview profile, so upgrading does not sign it out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebviewProfile {
+    Default,
+    /// Data directory (Windows, Linux) and data store identifier (macOS 14+, where WebKit
+    /// has no data directories) of the account
+    /// Older macOS silently ignores the identifier and would fall back to the default
+    /// profile, so there `supports_isolated_webviews` is false and accounts are refused
+    Isolated { data_dir: PathBuf, store_id: [u8; 16] },
+}
+
+/// Whether webviews can keep the cookies of each account apart
+pub fn supports_isolated_webviews() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        static SUPPORTED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *SUPPORTED.get_or_init(|| {
+            let version = std::process::Command::new("sw_vers")
+                .arg("-productVersion")
+                .output()
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+                .unwrap_or_default();
+            let supported = macos_major_version(&version).is_some_and(|major| major >= 14);
+            if !supported {
+                log::warn!("[Accounts] macOS {:?} cannot isolate account webviews", version.trim());
+            }
+            supported
+        })
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+/// Major version of a `sw_vers -productVersion` string such as "14.2.1"
+#[cfg(any(target_os = "macos", test))]
+fn macos_major_version(version: &str) -> Option<u32> {
+    version.trim().split('.').next()?.parse().ok()
+}
+
+/// Whether `id` has the shape `new_account_id` gives ids: lowercase letters, digits and dashes
+/// Ids name directories, so anything else is refused before touching the filesystem
+pub fn is_valid_account_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+}
+
+/// Data store identifier of account `id`, stable across launches
+pub fn webview_store_id(id: &str) -> [u8; 16] {
+    let digest = Sha256::digest(format!("copilot-tracker-account:{}", id).as_bytes());
+    let mut store_id = [0u8; 16];
+    store_id.copy_from_slice(&digest[..16]);
+    store_id
+}
+
+/// Used and limit summed over signed-in accounts
+pub fn aggregate_usage(usages: &[AccountUsage]) -> (u32, u32) {
+    usages
+        .iter()
+        .filter(|u| u.is_authenticated)
+        .fold((0u32, 0u32), |(used, limit), u| {
+            (used.saturating_add(u.used), limit.saturating_add(u.limit))
+        })
+}
+
+/// Stores of the accounts other than the default one, each with its own settings,
+/// usage cache and history database under `accounts/<id>`
+/// Opened on first use and kept for the lifetime of the app
+pub struct AccountStores {
+    app_dir: PathBuf,
+    stores: Mutex<HashMap<String, Arc<StoreManager>>>,
+}
+
+impl AccountStores {
+    pub fn new(app_dir: PathBuf) -> Self {
+        Self {
+            app_dir,
+            stores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Data directory of account `id`
+    pub fn account_dir(&self, id: &str) -> Result<PathBuf, String> {
+        if id == DEFAULT_ACCOUNT_ID {
+            return Ok(self.app_dir.clone());
+        }
+        if !is_valid_account_id(id) {
+            return Err(format!("Invalid account id: {:?}", id));
+        }
+        Ok(self.app_dir.join(ACCOUNTS_DIRNAME).join(id))
+    }
+
+    /// Webview profile of account `id`
+    pub fn webview_profile(&self, id: &str) -> Result<WebviewProfile, String> {
+        if id == DEFAULT_ACCOUNT_ID {
+            return Ok(WebviewProfile::Default);
+        }
+        Ok(WebviewProfile::Isolated {
+            data_dir: self.account_dir(id)?.join(WEBVIEW_DIRNAME),
+            store_id: webview_store_id(id),
+        })
+    }
+
+    /// Store of account `id`, which must not be the default account
+    pub fn open(&self, id: &str) -> Result<Arc<StoreManager>, String> {
+        if id == DEFAULT_ACCOUNT_ID {
+            return Err("The default account uses the main store".to_string());
+        }
+        let mut stores = self.stores.lock().unwrap();
+        if let Some(store) = stores.get(id) {
+            return Ok(store.clone());
+        }
+        let store = Arc::new(StoreManager::new(self.account_dir(id)?)?);
+        stores.insert(id.to_string(), store.clone());
+        Ok(store)
+    }
+
+    /// Close the store of account `id` and delete its data directory
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        if id == DEFAULT_ACCOUNT_ID {
+            return Err("The default account cannot be deleted".to_string());
+        }
+        let dir = self.account_dir(id)?;
+        self.stores.lock().unwrap().remove(id);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to delete account data {}: {}", dir.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Delete the data of every account other than the default one
+    pub fn delete_all(&self) -> Result<(), String> {
+        self.stores.lock().unwrap().clear();
+        let dir = self.app_dir.join(ACCOUNTS_DIRNAME);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to delete account data {}: {}", dir.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Run `f` with the store of account `id`, which must be one of the `accounts` setting
+pub fn with_account_store<T>(
+    app: &AppHandle,
+    id: &str,
+    f: impl FnOnce(&StoreManager) -> T,
+) -> Result<T, String> {
+    let store = app
+        .try_state::<StoreManager>()
+        .ok_or("StoreManager not available")?;
+    if id == DEFAULT_ACCOUNT_ID {
+        return Ok(f(&store));
+    }
+    if !store.get_settings().accounts.iter().any(|a| a.id == id) {
+        return Err(format!("Unknown account: {}", id));
+    }
+    let stores = app
+        .try_state::<AccountStores>()
+        .ok_or("Account stores not available")?;
+    let store = stores.open(id)?;
+    Ok(f(&store))
+}
+
+/// Account whose expired session the tray offers to renew: the displayed account when its
+/// session expired, otherwise the first account whose session did
+pub fn expired_account(app: &AppHandle) -> Option<AccountProfile> {
+    let settings = app.try_state::<StoreManager>()?.get_settings();
+    let expired: Vec<AccountProfile> = settings
+        .accounts
+        .into_iter()
+        .filter(|profile| {
+            with_account_store(app, &profile.id, |store| {
+                store.is_authenticated() && store.is_session_expired()
+            })
+            .unwrap_or(false)
+        })
+        .collect();
+    pick_expired_account(&settings.displayed_account, expired)
+}
+
+fn pick_expired_account(displayed: &str, expired: Vec<AccountProfile>) -> Option<AccountProfile> {
+    let displayed_index = expired.iter().position(|profile| profile.id == displayed);
+    expired.into_iter().nth(displayed_index.unwrap_or(0))
+}
+
+/// Display name of account `id`, or the id itself when it has no profile
+pub fn account_name(app: &AppHandle, id: &str) -> String {
+    app.try_state::<StoreManager>()
+        .and_then(|store| store.get_settings().accounts.into_iter().find(|a| a.id == id))
+        .map_or_else(|| id.to_string(), |profile| profile.name)
+}
+
+/// Latest usage of every account, in the order of the `accounts` setting
+pub fn account_usages(app: &AppHandle) -> Vec<AccountUsage> {
+    let Some(store) = app.try_state::<StoreManager>() else {
+        return Vec::new();
+    };
+    store
+        .get_settings()
+        .accounts
+        .into_iter()
+        .filter_map(|profile| {
+            let usage = with_account_store(app, &profile.id, |store| {
+                let (used, limit) = store.get_usage();
+                (store.is_authenticated() && !store.is_session_expired(), used, limit)
+            });
+            match usage {
+                Ok((is_authenticated, used, limit)) => Some(AccountUsage {
+                    id: profile.id,
+                    name: profile.name,
+                    is_authenticated,
+                    used,
+                    limit,
+                }),
+                Err(e) => {
+                    log::error!("[Accounts] Failed to open account {}: {}", profile.id, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn profile(id: &str) -> AccountProfile {
+        AccountProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn account_ids_are_unique_slugs() {
+        let existing = vec![AccountProfile::default_account(), profile("work")];
+        assert_eq!(new_account_id("Side Project!", &existing), "side-project");
+        assert_eq!(new_account_id("Work", &existing), "work-2");
+        assert_eq!(new_account_id("  ", &existing), "account");
+        assert_eq!(new_account_id("Default", &existing), "default-2");
+    }
+
+    #[test]
+    fn accounts_have_separate_webview_profiles() {
+        let stores = AccountStores::new(PathBuf::from("/data"));
+        assert_eq!(stores.webview_profile(DEFAULT_ACCOUNT_ID), Ok(WebviewProfile::Default));
+
+        let Ok(WebviewProfile::Isolated { data_dir, store_id }) = stores.webview_profile("work") else {
+            panic!("work account should have its own profile");
+        };
+        assert_eq!(data_dir, PathBuf::from("/data/accounts/work/webview"));
+        assert_eq!(store_id, webview_store_id("work"));
+        assert_ne!(store_id, webview_store_id("side-project"));
+    }
+
+    #[test]
+    fn refuses_path_traversal_ids() {
+        let dir = TempDir::new("accounts");
+        let stores = AccountStores::new(dir.0.clone());
+
+        for id in ["..", "../..", "../../..", "work/../..", "Work", "", "a b"] {
+            assert!(!is_valid_account_id(id), "{:?}", id);
+            assert!(stores.account_dir(id).is_err(), "{:?}", id);
+            assert!(stores.webview_profile(id).is_err(), "{:?}", id);
+            assert!(stores.open(id).is_err(), "{:?}", id);
+            assert!(stores.delete(id).is_err(), "{:?}", id);
+        }
+        assert!(dir.0.exists());
+        assert!(is_valid_account_id(&new_account_id("../../etc", &[])));
+    }
+
+    #[test]
+    fn prefers_the_displayed_expired_account() {
+        let expired = || vec![profile("default"), profile("work")];
+        assert_eq!(pick_expired_account("work", expired()), Some(profile("work")));
+        assert_eq!(pick_expired_account("side", expired()), Some(profile("default")));
+        assert_eq!(pick_expired_account("work", Vec::new()), None);
+    }
+
+    #[test]
+    fn aggregates_signed_in_accounts() {
+        let usage = |is_authenticated, used, limit| AccountUsage {
+            id: String::new(),
+            name: String::new(),
+            is_authenticated,
+            used,
+            limit,
+        };
+        let usages = [usage(true, 120, 300), usage(true, 40, 1000), usage(false, 7, 300)];
+        assert_eq!(aggregate_usage(&usages), (160, 1300));
+
+        let usages = [usage(true, u32::MAX, u32::MAX), usage(true, 1, 300)];
+        assert_eq!(aggregate_usage(&usages), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn reads_the_macos_major_version() {
+        assert_eq!(macos_major_version("14.2.1\n"), Some(14));
+        assert_eq!(macos_major_version("13.6"), Some(13));
+        assert_eq!(macos_major_version(""), None);
+    }
+
+    #[test]
+    fn unknown_displayed_account_falls_back_to_default() {
+        let mut settings = crate::store::AppSettings::default();
+        settings.accounts.push(profile("work"));
+        settings.displayed_account = "work".to_string();
+        assert_eq!(settings.displayed_account_id(), "work");
+
+        settings.displayed_account = "deleted".to_string();
+        assert_eq!(settings.displayed_account_id(), DEFAULT_ACCOUNT_ID);
+    }
+}
@@ -1,29 +1,43 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 use tokio::sync::{mpsc, Mutex as TokioMutex};
 use tokio::time::Duration;
 use url::Url;
 
+use crate::accounts::{self, AccountStores, WebviewProfile, DEFAULT_ACCOUNT_ID};
+use crate::billing_entity::BillingEntity;
+use crate::extractor::{self, extractor_script, ExtractorMode};
+use crate::money::{self, Money};
+use crate::update::GITHUB_RELEASES_API_URL;
+use crate::usage_query::{UsageGrouping, UsageQuery};
 use crate::StoreManager;
 
 /// Global channel for hidden webview events
 static HIDDEN_WEBVIEW_EVENTS: TokioMutex<Option<mpsc::Sender<HiddenWebviewEvent>>> = TokioMutex::const_new(None);
 
+/// Held for the whole of each hidden webview session
+/// Sessions share the channel above and the "hidden-auth" label, so a second one running at
+/// the same time (a poll and an entity refresh, or two accounts) would steal its events
+static HIDDEN_WEBVIEW_SESSION: TokioMutex<()> = TokioMutex::const_new(());
+
 #[derive(Debug, Clone)]
 pub struct HiddenWebviewEvent {
     pub event: String,
     pub payload: String,
 }
 
-const GITHUB_BILLING_URL: &str = "https://github.com/settings/billing";
+const GITHUB_URL: &str = "https://github.com";
 const GITHUB_LOGIN_URL: &str = "https://github.com/login";
-const GITHUB_API_URL: &str = "https://api.github.com/repos/bizzkoot/copilot-tracker/releases/latest";
 const EXTRACTION_TIMEOUT_SECS: u64 = 30;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthState {
     pub is_authenticated: bool,
     pub customer_id: Option<u64>,
+    /// Signed in before, but GitHub has since ended the session
+    #[serde(default)]
+    pub session_expired: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,7 +45,22 @@ pub struct ExtractionResult {
     pub customer_id: Option<u64>,
     pub usage_data: Option<UsageData>,
     pub usage_history: Option<Vec<UsageHistoryRow>>,
+    /// Per-model totals, set instead of `usage_history` for queries grouped by model
+    #[serde(default)]
+    pub usage_models: Option<Vec<UsageModelRow>>,
     pub error: Option<String>,
+    /// Set when the usage table no longer has the expected columns
+    #[serde(default)]
+    pub schema_drift: Option<SchemaDriftError>,
+    /// Version of the extractor script that produced the result
+    #[serde(default)]
+    pub extractor_version: Option<u32>,
+    /// Organizations and enterprises found alongside the personal account's usage
+    #[serde(default)]
+    pub billing_entities: Option<Vec<BillingEntity>>,
+    /// Set when GitHub sent the webview to its sign-in or two-factor page
+    #[serde(default)]
+    pub session_expired: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +70,9 @@ pub struct UsageHistoryRow {
     pub billed_requests: u32,
     pub gross_amount: f64,
     pub billed_amount: f64,
+    /// ISO 4217 code of the amounts, when the cells name one
+    #[serde(default)]
+    pub currency: Option<String>,
     pub models: Vec<UsageModelRow>,
 }
 
@@ -62,121 +94,492 @@ pub struct UsageData {
     pub filtered_user_premium_request_entitlement: u64,
 }
 
-/// Parses a single usage history row from JSON data
-/// Extracts date, request counts, amounts, and model breakdowns
-fn parse_usage_history_row(row: &serde_json::Value) -> Option<UsageHistoryRow> {
-    let id = row.get("id").and_then(|v| v.as_str())?.to_string();
-    let cells = row.get("cells").and_then(|v| v.as_array())?;
+/// Whether `url` is a GitHub sign-in, two-factor or device verification page, where GitHub
+/// sends a webview whose session has expired
+pub fn is_session_expired_url(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    if url.host_str() != Some("github.com") {
+        return false;
+    }
+    let path = url.path().trim_end_matches('/');
+    path == "/login"
+        || path == "/session"
+        || path.starts_with("/login/")
+        || path.starts_with("/sessions/")
+}
 
-    if cells.len() < 5 {
+/// Organizations and enterprises from an `auth:extraction:entities` payload
+/// `None` when the listing failed, so the entities known so far are left alone
+fn parse_billing_entities(payload: &serde_json::Value) -> Option<Vec<BillingEntity>> {
+    if !payload.get("success").and_then(|v| v.as_bool()).unwrap_or(false) {
+        log::warn!(
+            "Failed to list billing entities: {}",
+            payload.get("error").and_then(|v| v.as_str()).unwrap_or("unknown error")
+        );
         return None;
     }
 
-    let included_requests = cells
-        .get(1)?
-        .get("value")?
-        .as_str()?
-        .parse::<u32>()
-        .ok()?;
-
-    let billed_requests = cells
-        .get(2)?
-        .get("value")?
-        .as_str()?
-        .parse::<u32>()
-        .ok()?;
-
-    let gross_amount = cells
-        .get(3)?
-        .get("value")?
-        .as_str()?
-        .trim_start_matches('$')
-        .parse::<f64>()
-        .ok()?;
-
-    let billed_amount = cells
-        .get(4)?
-        .get("value")?
-        .as_str()?
-        .trim_start_matches('$')
-        .parse::<f64>()
-        .ok()?;
-
-    let models = if let Some(subtable) = row.get("subtable") {
-        if let Some(sub_rows) = subtable.get("rows").and_then(|v| v.as_array()) {
-            sub_rows
-                .iter()
-                .filter_map(parse_usage_model_row)
-                .collect()
+    let list = |key: &str, id: &str| -> Vec<String> {
+        payload
+            .get(key)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item.get(id).and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .collect()
+    };
+    let organizations = list("organizations", "login")
+        .into_iter()
+        .map(|login| BillingEntity::organization(&login, None));
+    let enterprises = list("enterprises", "slug")
+        .into_iter()
+        .map(|slug| BillingEntity::enterprise(&slug, None));
+    Some(organizations.chain(enterprises).collect())
+}
+
+/// Give a webview of `account` that account's cookies and site data, so signing in one
+/// account never replaces the session of another
+fn with_webview_profile<'a>(
+    builder: WebviewWindowBuilder<'a, tauri::Wry, AppHandle>,
+    app: &AppHandle,
+    account: &str,
+) -> Result<WebviewWindowBuilder<'a, tauri::Wry, AppHandle>, String> {
+    let profile = match app.try_state::<AccountStores>() {
+        Some(stores) => stores.webview_profile(account)?,
+        None => WebviewProfile::Default,
+    };
+    Ok(match profile {
+        WebviewProfile::Default => builder,
+        // Without isolation the webview would sign in over the default account's session
+        WebviewProfile::Isolated { .. } if !accounts::supports_isolated_webviews() => {
+            return Err(accounts::ISOLATION_UNSUPPORTED.to_string());
+        }
+        #[cfg(target_os = "macos")]
+        WebviewProfile::Isolated { store_id, .. } => builder.data_store_identifier(store_id),
+        #[cfg(not(target_os = "macos"))]
+        WebviewProfile::Isolated { data_dir, .. } => builder.data_directory(data_dir),
+    })
+}
+
+/// Store the sign-in of an account other than the default one in that account's store
+/// The tray and dashboard follow the default account, so only `accounts:updated` is emitted
+fn finish_account_sign_in(app: &AppHandle, account: &str, result: ExtractionResult) {
+    let applied = accounts::with_account_store(app, account, |store| {
+        crate::usage::UsageManager::new().apply_extraction(store, result)
+    });
+    match applied {
+        Ok(Some(outcome)) => log::info!(
+            "Signed in account {}: {}/{}",
+            account,
+            outcome.summary.used,
+            outcome.summary.limit
+        ),
+        Ok(None) => log::warn!("Signed in account {} without usage data", account),
+        Err(e) => log::error!("Failed to save sign-in of account {}: {}", account, e),
+    }
+    let _ = app.emit("accounts:updated", accounts::account_usages(app));
+
+    if let Some(auth_window) = app.get_webview_window("auth") {
+        let _ = auth_window.close();
+    }
+}
+
+/// Parses a `copilot_usage_card` response
+/// Missing or non-integer quantities read as 0
+fn parse_usage_card(card: &serde_json::Value) -> UsageData {
+    UsageData {
+        net_billed_amount: card.get("netBilledAmount").and_then(|v| v.as_f64()).unwrap_or(0.0),
+        net_quantity: card.get("netQuantity").and_then(|v| v.as_u64()).unwrap_or(0),
+        discount_quantity: card.get("discountQuantity").and_then(|v| v.as_u64()).unwrap_or(0),
+        user_premium_request_entitlement: card.get("userPremiumRequestEntitlement").and_then(|v| v.as_u64()).unwrap_or(0),
+        filtered_user_premium_request_entitlement: card.get("filteredUserPremiumRequestEntitlement").and_then(|v| v.as_u64()).unwrap_or(0),
+    }
+}
+
+/// The usage table's columns no longer match what the parser expects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDriftError {
+    /// "usage" for the daily table, "model" for the per-model subtables
+    pub table: String,
+    pub missing_columns: Vec<String>,
+    /// Header labels the response did contain
+    pub headers: Vec<String>,
+}
+
+impl std::fmt::Display for SchemaDriftError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Usage {} table is missing column(s) {} (found: {})",
+            self.table,
+            self.missing_columns.join(", "),
+            self.headers.join(", ")
+        )
+    }
+}
+
+/// Columns of the usage table the parser reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UsageColumn {
+    Label,
+    IncludedRequests,
+    BilledRequests,
+    GrossAmount,
+    BilledAmount,
+}
+
+impl UsageColumn {
+    const REQUIRED: [UsageColumn; 4] = [
+        UsageColumn::IncludedRequests,
+        UsageColumn::BilledRequests,
+        UsageColumn::GrossAmount,
+        UsageColumn::BilledAmount,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            UsageColumn::Label => "label",
+            UsageColumn::IncludedRequests => "includedRequests",
+            UsageColumn::BilledRequests => "billedRequests",
+            UsageColumn::GrossAmount => "grossAmount",
+            UsageColumn::BilledAmount => "billedAmount",
+        }
+    }
+
+    /// Identify a column from a header label or key, e.g. "Billed amount" or "billed_amount"
+    fn identify(header: &str) -> Option<Self> {
+        // Split on punctuation and camelCase boundaries
+        let mut words: Vec<String> = vec![String::new()];
+        let mut previous_lower = false;
+        for c in header.chars() {
+            if !c.is_ascii_alphanumeric() || (c.is_ascii_uppercase() && previous_lower) {
+                words.push(String::new());
+            }
+            if c.is_ascii_alphanumeric() {
+                words.last_mut().unwrap().push(c.to_ascii_lowercase());
+            }
+            previous_lower = c.is_ascii_lowercase();
+        }
+        let has = |word: &str| words.iter().any(|w| w.starts_with(word));
+
+        if has("included") {
+            Some(UsageColumn::IncludedRequests)
+        } else if has("gross") {
+            Some(UsageColumn::GrossAmount)
+        } else if has("billed") && (has("amount") || has("cost")) {
+            Some(UsageColumn::BilledAmount)
+        } else if has("billed") {
+            Some(UsageColumn::BilledRequests)
+        } else if has("date") || has("day") || has("model") || has("sku") || has("product") {
+            Some(UsageColumn::Label)
         } else {
-            vec![]
+            None
+        }
+    }
+}
+
+/// Cell index of each column the parser reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ColumnMap {
+    label: usize,
+    included_requests: usize,
+    billed_requests: usize,
+    gross_amount: usize,
+    billed_amount: usize,
+}
+
+impl ColumnMap {
+    /// Column order of responses without header metadata
+    const POSITIONAL: ColumnMap = ColumnMap {
+        label: 0,
+        included_requests: 1,
+        billed_requests: 2,
+        gross_amount: 3,
+        billed_amount: 4,
+    };
+
+    /// Map columns by their header; the label column defaults to the first one
+    fn from_headers(headers: &[serde_json::Value], table: &str) -> Result<Self, SchemaDriftError> {
+        let labels: Vec<String> = headers.iter().map(header_label).collect();
+        let position = |column: UsageColumn| {
+            headers
+                .iter()
+                .position(|h| header_keys(h).iter().any(|key| UsageColumn::identify(key) == Some(column)))
+        };
+
+        let missing: Vec<String> = UsageColumn::REQUIRED
+            .iter()
+            .filter(|c| position(**c).is_none())
+            .map(|c| c.name().to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(SchemaDriftError {
+                table: table.to_string(),
+                missing_columns: missing,
+                headers: labels,
+            });
+        }
+
+        Ok(ColumnMap {
+            label: position(UsageColumn::Label).unwrap_or(0),
+            included_requests: position(UsageColumn::IncludedRequests).unwrap(),
+            billed_requests: position(UsageColumn::BilledRequests).unwrap(),
+            gross_amount: position(UsageColumn::GrossAmount).unwrap(),
+            billed_amount: position(UsageColumn::BilledAmount).unwrap(),
+        })
+    }
+
+    /// Column map of a table, or `parent` when it carries no header metadata
+    fn for_table(
+        table: &serde_json::Value,
+        name: &str,
+        parent: ColumnMap,
+    ) -> Result<Self, SchemaDriftError> {
+        match table
+            .get("headers")
+            .or_else(|| table.get("columns"))
+            .and_then(|v| v.as_array())
+        {
+            Some(headers) if !headers.is_empty() => Self::from_headers(headers, name),
+            _ => Ok(parent),
+        }
+    }
+
+    /// Number of cells a row needs to hold every mapped column
+    fn width(&self) -> usize {
+        [
+            self.label,
+            self.included_requests,
+            self.billed_requests,
+            self.gross_amount,
+            self.billed_amount,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+            + 1
+    }
+}
+
+/// Strings a header may be identified by: its key fields first, then its display text
+fn header_keys(header: &serde_json::Value) -> Vec<&str> {
+    match header {
+        serde_json::Value::String(s) => vec![s.as_str()],
+        _ => ["id", "key", "field", "value", "label", "name", "title"]
+            .iter()
+            .filter_map(|f| header.get(*f).and_then(|v| v.as_str()))
+            .collect(),
+    }
+}
+
+/// Display text of a header, for error reports
+fn header_label(header: &serde_json::Value) -> String {
+    ["value", "label", "name", "title", "id", "key", "field"]
+        .iter()
+        .find_map(|f| header.get(*f).and_then(|v| v.as_str()))
+        .or_else(|| header.as_str())
+        .unwrap_or("?")
+        .to_string()
+}
+
+/// Text of the cell at `index`
+fn cell_value(cells: &[serde_json::Value], index: usize) -> Option<&str> {
+    cells.get(index)?.get("value")?.as_str()
+}
+
+fn parse_count(cells: &[serde_json::Value], index: usize) -> Option<u32> {
+    money::parse_count(cell_value(cells, index)?)
+}
+
+fn parse_amount(cells: &[serde_json::Value], index: usize) -> Option<Money> {
+    money::parse_money(cell_value(cells, index)?)
+}
+
+/// Currency shared by a row's amounts; the first one named wins if they disagree
+fn row_currency(amounts: [&Money; 2]) -> Option<String> {
+    let mut named = amounts.iter().filter_map(|m| m.currency.as_deref());
+    let currency = named.next()?;
+    if let Some(other) = named.find(|c| *c != currency) {
+        log::warn!("Usage row mixes currencies {} and {}", currency, other);
+    }
+    Some(currency.to_string())
+}
+
+/// Parses every fetched page of a `copilot_usage_table` response into one history
+/// Columns are mapped by the table's header metadata, falling back to the historical
+/// column order when the response has none; pages without it reuse the columns of the page before
+/// Returns `Ok(None)` when no page has a row list; rows that fail to parse are skipped
+fn parse_usage_table_pages(
+    pages: &[&serde_json::Value],
+) -> Result<Option<Vec<UsageHistoryRow>>, SchemaDriftError> {
+    let mut columns = ColumnMap::POSITIONAL;
+    let mut history: Option<Vec<UsageHistoryRow>> = None;
+
+    for (index, page) in pages.iter().enumerate() {
+        let Some(table) = page.get("table") else {
+            continue;
+        };
+        columns = ColumnMap::for_table(table, "usage", columns)?;
+        let Some(rows) = table.get("rows").and_then(|v| v.as_array()) else {
+            continue;
+        };
+        log::info!("Parsing usage history page {}, found {} rows", index + 1, rows.len());
+
+        let parsed = history.get_or_insert_with(Vec::new);
+        for row in rows {
+            if let Some(row) = parse_usage_history_row(row, &columns)? {
+                parsed.push(row);
+            }
+        }
+    }
+
+    Ok(history.map(merge_usage_rows))
+}
+
+/// Parses every fetched page of a usage table grouped by model
+/// A model listed on more than one page keeps its first row
+fn parse_usage_model_table_pages(
+    pages: &[&serde_json::Value],
+) -> Result<Option<Vec<UsageModelRow>>, SchemaDriftError> {
+    let mut columns = ColumnMap::POSITIONAL;
+    let mut models: Option<Vec<UsageModelRow>> = None;
+
+    for page in pages {
+        let Some(table) = page.get("table") else {
+            continue;
+        };
+        columns = ColumnMap::for_table(table, "model", columns)?;
+        let Some(rows) = table.get("rows").and_then(|v| v.as_array()) else {
+            continue;
+        };
+
+        let parsed = models.get_or_insert_with(Vec::new);
+        for model in rows.iter().filter_map(|row| parse_usage_model_row(row, &columns)) {
+            if !parsed.iter().any(|m| m.name == model.name) {
+                parsed.push(model);
+            }
+        }
+    }
+
+    Ok(models)
+}
+
+/// Pages of an extracted `usageTable`: every page the script fetched, or `data` alone
+/// for results that predate pagination
+fn usage_table_pages(usage_table: &serde_json::Value) -> Vec<&serde_json::Value> {
+    match usage_table.get("pages").and_then(|v| v.as_array()) {
+        Some(pages) if !pages.is_empty() => pages.iter().collect(),
+        _ => usage_table.get("data").into_iter().collect(),
+    }
+}
+
+/// Collapse rows repeated across pages into one row per date
+/// The first copy keeps its totals; models split across pages are combined by name
+fn merge_usage_rows(rows: Vec<UsageHistoryRow>) -> Vec<UsageHistoryRow> {
+    let mut merged: Vec<UsageHistoryRow> = Vec::with_capacity(rows.len());
+    let mut index_by_date: HashMap<String, usize> = HashMap::new();
+
+    for row in rows {
+        match index_by_date.get(&row.date) {
+            Some(&index) => {
+                log::debug!("Merging duplicate usage row for {}", row.date);
+                let existing = &mut merged[index];
+                for model in row.models {
+                    if !existing.models.iter().any(|m| m.name == model.name) {
+                        existing.models.push(model);
+                    }
+                }
+            }
+            None => {
+                index_by_date.insert(row.date.clone(), merged.len());
+                merged.push(row);
+            }
         }
-    } else {
-        vec![]
+    }
+
+    merged
+}
+
+/// Parses a single usage history row from JSON data
+/// Extracts date, request counts, amounts, and model breakdowns
+/// Model subtables without their own headers share the parent table's columns
+fn parse_usage_history_row(
+    row: &serde_json::Value,
+    columns: &ColumnMap,
+) -> Result<Option<UsageHistoryRow>, SchemaDriftError> {
+    let (Some(id), Some(cells)) = (
+        row.get("id").and_then(|v| v.as_str()),
+        row.get("cells").and_then(|v| v.as_array()),
+    ) else {
+        return Ok(None);
+    };
+
+    if cells.len() < columns.width() {
+        log::debug!("Skipping usage row {} with {} cells", id, cells.len());
+        return Ok(None);
+    }
+
+    let (Some(included_requests), Some(billed_requests), Some(gross_amount), Some(billed_amount)) = (
+        parse_count(cells, columns.included_requests),
+        parse_count(cells, columns.billed_requests),
+        parse_amount(cells, columns.gross_amount),
+        parse_amount(cells, columns.billed_amount),
+    ) else {
+        log::debug!("Skipping usage row {} with unparseable cells", id);
+        return Ok(None);
     };
 
-    Some(UsageHistoryRow {
-        date: id,
+    let models = match row.get("subtable") {
+        Some(subtable) => match subtable.get("rows").and_then(|v| v.as_array()) {
+            Some(sub_rows) => {
+                let sub_columns = ColumnMap::for_table(subtable, "model", *columns)?;
+                sub_rows
+                    .iter()
+                    .filter_map(|sub_row| parse_usage_model_row(sub_row, &sub_columns))
+                    .collect()
+            }
+            None => vec![],
+        },
+        None => vec![],
+    };
+
+    Ok(Some(UsageHistoryRow {
+        date: id.to_string(),
         included_requests,
         billed_requests,
-        gross_amount,
-        billed_amount,
+        gross_amount: gross_amount.amount,
+        billed_amount: billed_amount.amount,
+        currency: row_currency([&gross_amount, &billed_amount]),
         models,
-    })
+    }))
 }
 
 /// Parses a single usage model row from JSON data
 /// Extracts model name, request counts, and amounts
-fn parse_usage_model_row(sub_row: &serde_json::Value) -> Option<UsageModelRow> {
+fn parse_usage_model_row(sub_row: &serde_json::Value, columns: &ColumnMap) -> Option<UsageModelRow> {
     let sub_cells = sub_row.get("cells").and_then(|v| v.as_array())?;
-    if sub_cells.len() < 5 {
+    if sub_cells.len() < columns.width() {
         return None;
     }
 
-    let name = sub_cells
-        .first()?
-        .get("value")?
-        .as_str()?
-        .to_string();
-    let included_requests = sub_cells
-        .get(1)?
-        .get("value")?
-        .as_str()?
-        .parse::<u32>()
-        .ok()?;
-    let billed_requests = sub_cells
-        .get(2)?
-        .get("value")?
-        .as_str()?
-        .parse::<u32>()
-        .ok()?;
-    let gross_amount = sub_cells
-        .get(3)?
-        .get("value")?
-        .as_str()?
-        .trim_start_matches('$')
-        .parse::<f64>()
-        .ok()?;
-    let billed_amount = sub_cells
-        .get(4)?
-        .get("value")?
-        .as_str()?
-        .trim_start_matches('$')
-        .parse::<f64>()
-        .ok()?;
-
     Some(UsageModelRow {
-        name,
-        included_requests,
-        billed_requests,
-        gross_amount,
-        billed_amount,
+        name: cell_value(sub_cells, columns.label)?.to_string(),
+        included_requests: parse_count(sub_cells, columns.included_requests)?,
+        billed_requests: parse_count(sub_cells, columns.billed_requests)?,
+        gross_amount: parse_amount(sub_cells, columns.gross_amount)?.amount,
+        billed_amount: parse_amount(sub_cells, columns.billed_amount)?.amount,
     })
 }
 
 #[derive(Clone)]
 pub struct AuthManager {
     auth_window: Option<tauri::WebviewWindow>,
+    /// Account whose webview profile the auth window and hidden webview use
+    account: String,
     customer_id: Option<u64>,
     extraction_in_progress: bool,
     auth_window_listener_attached: bool,
@@ -186,14 +589,31 @@ impl AuthManager {
     pub fn new() -> Self {
         Self {
             auth_window: None,
+            account: DEFAULT_ACCOUNT_ID.to_string(),
             customer_id: None,
             extraction_in_progress: false,
             auth_window_listener_attached: false,
         }
     }
 
-    /// Create or show the auth webview window
-    pub fn show_auth_window(&mut self, app: &AppHandle) -> Result<(), String> {
+    /// Manager whose webviews use the session of `account`
+    pub fn for_account(account: &str) -> Self {
+        Self {
+            account: account.to_string(),
+            ..Self::new()
+        }
+    }
+
+    /// Create or show the auth webview window signing in `account`
+    pub fn show_auth_window(&mut self, app: &AppHandle, account: &str) -> Result<(), String> {
+        // A window signing in another account is replaced
+        if self.account != account {
+            if let Some(window) = self.auth_window.take() {
+                let _ = window.destroy();
+            }
+            self.account = account.to_string();
+        }
+
         // If window exists, just show it
         if let Some(window) = &self.auth_window {
             if window.is_visible().unwrap_or(false) {
@@ -214,7 +634,8 @@ impl AuthManager {
             .map_err(|e| format!("Failed to parse URL: {}", e))?;
 
         let app_handle = app.clone();
-        let window = WebviewWindowBuilder::new(app, "auth", WebviewUrl::External(url))
+        let account = account.to_string();
+        let builder = WebviewWindowBuilder::new(app, "auth", WebviewUrl::External(url))
         .on_navigation(move |url| {
             let url_str = url.as_str();
 
@@ -225,12 +646,16 @@ impl AuthManager {
                 let mut extracted_id = None;
                 let mut extracted_usage_data = None;
                 let mut extracted_usage_history = None;
+                let mut schema_drift = None;
+                let mut extractor_version = None;
 
                 // Try to parse from hash payload first (new method)
                 if let Some(fragment) = url.fragment() {
                     if let Some(encoded) = fragment.strip_prefix("payload=") {
                         if let Ok(decoded) = urlencoding::decode(encoded) {
                             if let Ok(json) = serde_json::from_str::<serde_json::Value>(&decoded) {
+                                extractor_version = extractor::reported_version(&json);
+
                                 // Extract ID
                                 if let Some(id) = json.get("id").and_then(|v| v.as_u64()) {
                                     extracted_id = Some(id);
@@ -238,27 +663,19 @@ impl AuthManager {
                                     // Extract Usage Data
                                     if let Some(usage_card) = json.get("usageCard").and_then(|v| v.get("data")) {
                                         log::info!("Raw usage card data: {:?}", usage_card);
-                                        extracted_usage_data = Some(UsageData {
-                                            net_billed_amount: usage_card.get("netBilledAmount").and_then(|v| v.as_f64()).unwrap_or(0.0),
-                                            net_quantity: usage_card.get("netQuantity").and_then(|v| v.as_u64()).unwrap_or(0),
-                                            discount_quantity: usage_card.get("discountQuantity").and_then(|v| v.as_u64()).unwrap_or(0),
-                                            user_premium_request_entitlement: usage_card.get("userPremiumRequestEntitlement").and_then(|v| v.as_u64()).unwrap_or(0),
-                                            filtered_user_premium_request_entitlement: usage_card.get("filteredUserPremiumRequestEntitlement").and_then(|v| v.as_u64()).unwrap_or(0),
-                                        });
+                                        extracted_usage_data = Some(parse_usage_card(usage_card));
                                     }
 
                                     // Extract Usage History
-                                    if let Some(rows) = json.get("usageTable")
-                                        .and_then(|v| v.get("data"))
-                                        .and_then(|v| v.get("table"))
-                                        .and_then(|v| v.get("rows"))
-                                        .and_then(|v| v.as_array()) 
-                                    {
-                                        log::info!("Parsing usage history, found {} rows", rows.len());
-                                        let history: Vec<UsageHistoryRow> = rows.iter().filter_map(parse_usage_history_row).collect();
-                                        
-                                        log::info!("Successfully parsed {} history rows", history.len());
-                                        extracted_usage_history = Some(history);
+                                    if let Some(usage_table) = json.get("usageTable") {
+                                        match parse_usage_table_pages(&usage_table_pages(usage_table)) {
+                                            Ok(Some(history)) => {
+                                                log::info!("Successfully parsed {} history rows", history.len());
+                                                extracted_usage_history = Some(history);
+                                            }
+                                            Ok(None) => {}
+                                            Err(drift) => schema_drift = Some(drift),
+                                        }
                                     }
                                 }
                             }
@@ -276,91 +693,34 @@ impl AuthManager {
                 }
 
                 if let Some(id) = extracted_id {
+                     if let Some(drift) = &schema_drift {
+                         crate::usage::UsageManager::report_schema_drift(&app_handle, drift);
+                     }
+                     let result = ExtractionResult {
+                         customer_id: Some(id),
+                         usage_data: extracted_usage_data,
+                         usage_history: extracted_usage_history,
+                         usage_models: None,
+                         error: None,
+                         schema_drift,
+                         extractor_version,
+                         billing_entities: None,
+                         session_expired: false,
+                     };
+                     if account != DEFAULT_ACCOUNT_ID {
+                         finish_account_sign_in(&app_handle, &account, result);
+                         return false;
+                     }
+
                      let store = app_handle.state::<StoreManager>();
                      if store.set_customer_id(id).is_ok() {
                          log::info!("Successfully authenticated with Customer ID: {}", id);
                          
                          // Save usage data and history
-                          let mut usage_summary = None;
-                          let mut usage_entries = vec![];
-
-                          if let Some(usage) = extracted_usage_data {
-                              log::info!("Extracted usage data: net_quantity={}, discount_quantity={}, entitlement={}", 
-                                  usage.net_quantity, usage.discount_quantity, usage.user_premium_request_entitlement);
-                              
-                              let used = usage.discount_quantity as u32;
-                              let limit = usage.user_premium_request_entitlement as u32;
-                              
-                              if used == 0 && limit == 0 {
-                                  log::warn!("Usage data shows 0/0 - API may have returned empty data");
-                              }
-                              
-                              let _ = store.set_usage(used, limit);
-
-                              // Update cache
-                              let cache = crate::store::UsageCache {
-                                  customer_id: id,
-                                  net_quantity: usage.net_quantity,
-                                  discount_quantity: usage.discount_quantity,
-                                  user_premium_request_entitlement: usage.user_premium_request_entitlement,
-                                  filtered_user_premium_request_entitlement: usage.filtered_user_premium_request_entitlement,
-                                  net_billed_amount: usage.net_billed_amount,
-                                  timestamp: chrono::Utc::now().timestamp(),
-                              };
-                              store.set_usage_cache(cache);
-                              
-                              // Create summary
-                              let remaining = limit.saturating_sub(used);
-                              let percentage = if limit > 0 { (used as f32 / limit as f32) * 100.0 } else { 0.0 };
-                              usage_summary = Some(crate::usage::UsageSummary {
-                                  used,
-                                  limit,
-                                  remaining,
-                                  percentage,
-                                  timestamp: chrono::Utc::now().timestamp(),
-                              });
-                          } else {
-                              log::warn!("No usage data was extracted from GitHub API");
-                          }
-
-                          // Save history
-                          if let Some(rows) = extracted_usage_history {
-                              log::info!("Extracted {} usage history rows", rows.len());
-                              usage_entries = crate::usage::UsageManager::map_history_rows(&rows);
-                              store.set_usage_history(usage_entries.clone());
-                          } else {
-                              log::warn!("No usage history was extracted from GitHub API");
-                          }
-
-                          // Emit full usage:data payload with prediction
-                          if let Some(summary) = usage_summary {
-                              let history = if !usage_entries.is_empty() {
-                                  usage_entries
-                              } else {
-                                  crate::usage::UsageManager::get_cached_history(&app_handle)
-                              };
-                              
-                              let settings = store.get_settings();
-                              let prediction = crate::usage::UsageManager::predict_usage_from_history(
-                                  &history,
-                                  summary.used,
-                                  summary.limit,
-                                  settings.prediction_period,
-                              );
-                              
-                              log::info!("Emitting usage:data event - used: {}, limit: {}, history entries: {}", 
-                                  summary.used, summary.limit, history.len());
-                              
-                              let payload = crate::usage::UsagePayload {
-                                  summary: summary.clone(),
-                                  history,
-                                  prediction,
-                              };
-                              
-                              let _ = app_handle.emit("usage:data", payload);
-                              let _ = app_handle.emit("usage:updated", &summary);
-                          } else {
-                              log::warn!("No usage summary to emit - authentication succeeded but no usage data available");
+                          let usage_manager = crate::usage::UsageManager::new();
+                          match usage_manager.apply_extraction(&store, result) {
+                              Some(outcome) => usage_manager.publish(&app_handle, &outcome),
+                              None => log::warn!("No usage summary to emit - authentication succeeded but no usage data available"),
                           }
 
                          let _ = app_handle.emit("auth:state-changed", "authenticated");
@@ -410,214 +770,14 @@ impl AuthManager {
         .inner_size(900.0, 700.0)
         .resizable(true)
         .visible(true)
-        .initialization_script(r#"
-            (function() {
-              console.log('[AuthInjector] Script loaded');
-
-              // Monitor URL changes for billing page detection
-              let currentUrl = location.href;
-              console.log('[AuthInjector] Initial URL:', currentUrl);
-              
-              function checkUrl() {
-                const newUrl = location.href;
-                if (newUrl === 'https://github.com/' || newUrl === 'https://github.com') {
-                  console.log('[AuthInjector] Detected homepage, redirecting to billing...');
-                  window.location.href = 'https://github.com/settings/billing';
-                }
-
-                if (newUrl !== currentUrl) {
-                  currentUrl = newUrl;
-                  console.log('[AuthInjector] URL changed to:', currentUrl);
-                  if (currentUrl.includes('/settings/billing')) {
-                    console.log('[AuthInjector] Billing page detected, starting extraction in 1.5s');
-                    // Page changed to billing - start extraction
-                    setTimeout(extractAndSend, 1500);
-                  }
-                }
-              }
-              
-              // Monitor URL changes using MutationObserver
-              const urlObserver = new MutationObserver(function() {
-                checkUrl();
-              });
-              
-              // Observe changes to the document
-              urlObserver.observe(document, { subtree: true, childList: true });
-              
-              // Also check on popstate events
-              window.addEventListener('popstate', checkUrl);
-              window.addEventListener('hashchange', checkUrl);
-              
-              if (location.href === 'https://github.com/' || location.href === 'https://github.com') {
-                console.log('[AuthInjector] Detected homepage, redirecting to billing...');
-                window.location.href = 'https://github.com/settings/billing';
-              }
-
-              // Check immediately if already on billing page
-              if (location.href.includes('/settings/billing')) {
-                console.log('[AuthInjector] Already on billing page, starting extraction in 1.5s');
-                setTimeout(extractAndSend, 1500);
-              }
-              
-              async function getUserId() {
-                console.log('[AuthInjector] Attempting to get User ID via API...');
-                try {
-                  const response = await fetch('/api/v3/user', {
-                    headers: { 'Accept': 'application/json' }
-                  });
-                  console.log('[AuthInjector] API Response Status:', response.status);
-                  if (!response.ok) {
-                    console.error('[AuthInjector] API request failed:', response.status);
-                    return { success: false, error: 'API request failed: ' + response.status };
-                  }
-                  const data = await response.json();
-                  console.log('[AuthInjector] User ID retrieved:', data.id);
-                  return { success: true, id: data.id };
-                } catch (error) {
-                  console.error('[AuthInjector] API request error:', error);
-                  return { success: false, error: error.message };
-                }
-              }
-              
-              function getCustomerIdFromDOM() {
-                console.log('[AuthInjector] Attempting to get Customer ID from DOM...');
-                try {
-                  const el = document.querySelector('script[data-target="react-app.embeddedData"]');
-                  if (!el) {
-                    console.log('[AuthInjector] Embedded data element not found');
-                    return { success: false, error: 'Embedded data element not found' };
-                  }
-                  const data = JSON.parse(el.textContent);
-                  const customerId = data?.payload?.customer?.customerId;
-                  if (!customerId) {
-                    console.log('[AuthInjector] Customer ID not found in embedded data');
-                    return { success: false, error: 'Customer ID not found in embedded data' };
-                  }
-                  console.log('[AuthInjector] Customer ID found in DOM:', customerId);
-                  return { success: true, id: customerId };
-                } catch (error) {
-                  console.error('[AuthInjector] DOM extraction error:', error);
-                  return { success: false, error: error.message };
-                }
-              }
-              
-              function getCustomerIdFromHTML() {
-                console.log('[AuthInjector] Attempting to get Customer ID from HTML regex...');
-                try {
-                  const html = document.body.innerHTML;
-                  const patterns = [
-                    /customerId":(\d+)/,
-                    /customerId&quot;:(\d+)/,
-                    /customer_id=(\d+)/,
-                    /"customerId":(\d+)/,
-                    /data-customer-id="(\d+)"/
-                  ];
-                  for (const pattern of patterns) {
-                    const match = html.match(pattern);
-                    if (match && match[1]) {
-                      console.log('[AuthInjector] Customer ID matched pattern:', pattern);
-                      return { success: true, id: parseInt(match[1]) };
-                    }
-                  }
-                  console.log('[AuthInjector] No customer ID pattern matched');
-                  return { success: false, error: 'No customer ID pattern matched' };
-                } catch (error) {
-                  console.error('[AuthInjector] HTML extraction error:', error);
-                  return { success: false, error: error.message };
-                }
-              }
-              
-              async function extractCustomerId() {
-                console.log('[AuthInjector] Starting extraction chain...');
-                let result = await getUserId();
-                if (!result.success) {
-                  result = getCustomerIdFromDOM();
-                }
-                if (!result.success) {
-                  result = getCustomerIdFromHTML();
-                }
-                return result;
-              }
-
-              async function fetchUsageCard(customerId) {
-                try {
-                  console.log('[AuthInjector] Fetching usage card for customer:', customerId);
-                  const res = await fetch(`/settings/billing/copilot_usage_card?customer_id=${customerId}&period=3`, {
-                    headers: {
-                      'Accept': 'application/json',
-                      'x-requested-with': 'XMLHttpRequest'
-                    }
-                  });
-                  console.log('[AuthInjector] Usage card response status:', res.status);
-                  if (!res.ok) {
-                    console.error('[AuthInjector] Usage card request failed:', res.status);
-                    return { success: false, error: 'Usage card request failed: ' + res.status };
-                  }
-                  const data = await res.json();
-                  console.log('[AuthInjector] Usage card data received:', data ? 'YES' : 'NO', 'Keys:', data ? Object.keys(data) : []);
-                  return { success: true, data };
-                } catch (error) {
-                  console.error('[AuthInjector] Usage card fetch error:', error);
-                  return { success: false, error: error.message };
-                }
-              }
-
-              async function fetchUsageTable(customerId) {
-                try {
-                  console.log('[AuthInjector] Fetching usage table for customer:', customerId);
-                  const res = await fetch(`/settings/billing/copilot_usage_table?customer_id=${customerId}&group=0&period=3&query=&page=1`, {
-                    headers: {
-                      'Accept': 'application/json',
-                      'x-requested-with': 'XMLHttpRequest'
-                    }
-                  });
-                  console.log('[AuthInjector] Usage table response status:', res.status);
-                  if (!res.ok) {
-                    console.error('[AuthInjector] Usage table request failed:', res.status);
-                    return { success: false, error: 'Usage table request failed: ' + res.status };
-                  }
-                  const data = await res.json();
-                  console.log('[AuthInjector] Usage table data received:', data ? 'YES' : 'NO', 'Rows:', data?.data?.rows?.length || 0);
-                  return { success: true, data };
-                } catch (error) {
-                  console.error('[AuthInjector] Usage table fetch error:', error);
-                  return { success: false, error: error.message };
-                }
-              }
-              
-              async function extractAndSend() {
-                console.log('[AuthInjector] Running extractAndSend...');
-                const result = await extractCustomerId();
-                if (result.success && result.id) {
-                  console.log('[AuthInjector] Extraction success, ID:', result.id, 'fetching usage data...');
-                  
-                  const usageCard = await fetchUsageCard(result.id);
-                  const usageTable = await fetchUsageTable(result.id);
-                  
-                  console.log('[AuthInjector] Creating payload...');
-                  const payload = {
-                      id: result.id,
-                      usageCard: usageCard,
-                      usageTable: usageTable
-                  };
-                  
-                  console.log('[AuthInjector] Redirecting with payload...');
-                  const hash = encodeURIComponent(JSON.stringify(payload));
-                  window.location.href = "https://copilot-auth-success.local/success#payload=" + hash;
-                } else {
-                  console.error('[AuthInjector] Failed to extract customer ID:', result.error);
-                  // Emit event to notify renderer of extraction failure
-                  if (window.__TAURI__?.event) {
-                    window.__TAURI__.event.emit('auth:extraction-failed', { 
-                      error: result.error || 'Unknown extraction error' 
-                    });
-                  }
-                }
-              }
-            })();
-        "#)
-        .build()
-        .map_err(|e| format!("Failed to create auth window: {}", e))?;
+        .initialization_script(extractor_script(
+            ExtractorMode::Auth,
+            &BillingEntity::personal(),
+            &UsageQuery::default(),
+        ));
+        let window = with_webview_profile(builder, app, &self.account)?
+            .build()
+            .map_err(|e| format!("Failed to create auth window: {}", e))?;
 
         self.auth_window = Some(window);
         Ok(())
@@ -666,10 +826,17 @@ impl AuthManager {
     pub fn create_hidden_webview(
         &mut self,
         app: &AppHandle,
+        entity: &BillingEntity,
+        query: &UsageQuery,
     ) -> Result<tauri::WebviewWindow, String> {
-        let url = Url::parse(GITHUB_BILLING_URL)
+        let url = Url::parse(&format!("{}{}/settings/billing", GITHUB_URL, entity.path_prefix()))
             .map_err(|e| format!("Failed to parse URL: {}", e))?;
 
+        // A window of an earlier session may still be closing and would hold the label
+        if let Some(stale) = app.get_webview_window("hidden-auth") {
+            let _ = stale.destroy();
+        }
+
         let builder = WebviewWindowBuilder::new(
             app,
             "hidden-auth",
@@ -702,164 +869,8 @@ impl AuthManager {
             .position(-100.0, -100.0)
             .visible(true);
 
-        let window = builder
-        .initialization_script(r#"
-            (function() {
-              console.log('[HiddenAuth] Script initialized');
-              
-              async function sendResult(kind, payload) {
-                try {
-                  // Tauri v2 event emission via core invoke
-                  if (window.__TAURI__ && window.__TAURI__.core) {
-                    await window.__TAURI__.core.invoke('hidden_webview_event', { 
-                      event: kind, 
-                      payload: JSON.stringify(payload) 
-                    });
-                    console.log('[HiddenAuth] Sent event:', kind);
-                  } else {
-                    console.error('[HiddenAuth] Tauri not available');
-                    // Fallback: store in localStorage for parent window to pick up
-                    localStorage.setItem('tauri_hidden_webview_' + kind, JSON.stringify(payload));
-                  }
-                } catch (e) {
-                  console.error('[HiddenAuth] Failed to send:', e);
-                }
-              }
-
-              async function getUserId() {
-                try {
-                  const response = await fetch('/api/v3/user', {
-                    headers: { 'Accept': 'application/json' }
-                  });
-                  if (!response.ok) {
-                    return { success: false, error: 'API request failed: ' + response.status };
-                  }
-                  const data = await response.json();
-                  return { success: true, id: data.id };
-                } catch (error) {
-                  return { success: false, error: error.message };
-                }
-              }
-
-              function getCustomerIdFromDOM() {
-                try {
-                  const el = document.querySelector('script[data-target="react-app.embeddedData"]');
-                  if (!el) {
-                    return { success: false, error: 'Embedded data element not found' };
-                  }
-                  const data = JSON.parse(el.textContent);
-                  const customerId = data?.payload?.customer?.customerId;
-                  if (!customerId) {
-                    return { success: false, error: 'Customer ID not found in embedded data' };
-                  }
-                  return { success: true, id: customerId };
-                } catch (error) {
-                  return { success: false, error: error.message };
-                }
-              }
-
-              function getCustomerIdFromHTML() {
-                try {
-                  const html = document.body.innerHTML;
-                  const patterns = [
-                    /customerId":(\d+)/,
-                    /customerId&quot;:(\d+)/,
-                    /customer_id=(\d+)/,
-                    /"customerId":(\d+)/,
-                    /data-customer-id=\"(\d+)\"/
-                  ];
-                  for (const pattern of patterns) {
-                    const match = html.match(pattern);
-                    if (match && match[1]) {
-                      return { success: true, id: parseInt(match[1]) };
-                    }
-                  }
-                  return { success: false, error: 'No customer ID pattern matched' };
-                } catch (error) {
-                  return { success: false, error: error.message };
-                }
-              }
-
-              async function extractCustomerId() {
-                let result = await getUserId();
-                if (!result.success) {
-                  result = getCustomerIdFromDOM();
-                }
-                if (!result.success) {
-                  result = getCustomerIdFromHTML();
-                }
-                return result;
-              }
-
-              async function fetchUsageCard(customerId) {
-                try {
-                  const res = await fetch(`/settings/billing/copilot_usage_card?customer_id=${customerId}&period=3`, {
-                    headers: {
-                      'Accept': 'application/json',
-                      'x-requested-with': 'XMLHttpRequest'
-                    }
-                  });
-                  if (!res.ok) {
-                    return { success: false, error: 'Usage card request failed: ' + res.status };
-                  }
-                  const data = await res.json();
-                  return { success: true, data };
-                } catch (error) {
-                  return { success: false, error: error.message };
-                }
-              }
-
-              async function fetchUsageTable(customerId) {
-                try {
-                  const res = await fetch(`/settings/billing/copilot_usage_table?customer_id=${customerId}&group=0&period=3&query=&page=1`, {
-                    headers: {
-                      'Accept': 'application/json',
-                      'x-requested-with': 'XMLHttpRequest'
-                    }
-                  });
-                  if (!res.ok) {
-                    return { success: false, error: 'Usage table request failed: ' + res.status };
-                  }
-                  const data = await res.json();
-                  return { success: true, data };
-                } catch (error) {
-                  return { success: false, error: error.message };
-                }
-              }
-
-              async function runExtraction() {
-                console.log('[HiddenAuth] Starting extraction...');
-                const customerResult = await extractCustomerId();
-                console.log('[HiddenAuth] Customer result:', customerResult);
-                await sendResult('auth:extraction:customer', customerResult);
-
-                if (!customerResult.success) {
-                  await sendResult('auth:extraction:complete', { success: false });
-                  return;
-                }
-
-                console.log('[HiddenAuth] Fetching usage data...');
-                const usageCard = await fetchUsageCard(customerResult.id);
-                const usageTable = await fetchUsageTable(customerResult.id);
-                
-                await sendResult('auth:extraction:usage', { 
-                  customerId: customerResult.id,
-                  usageCard, 
-                  usageTable 
-                });
-                
-                await sendResult('auth:extraction:complete', { success: true });
-                console.log('[HiddenAuth] Extraction complete');
-              }
-
-              // Run extraction when page is ready
-              if (document.readyState === 'complete') {
-                setTimeout(runExtraction, 1500);
-              } else {
-                window.addEventListener('load', () => setTimeout(runExtraction, 1500));
-              }
-            })();
-        "#)
+        let window = with_webview_profile(builder, app, &self.account)?
+        .initialization_script(extractor_script(ExtractorMode::Hidden, entity, query))
         .build()
         .map_err(|e| format!("Failed to create hidden webview: {}", e))?;
 
@@ -871,6 +882,18 @@ impl AuthManager {
         &mut self,
         app: &AppHandle,
     ) -> Result<ExtractionResult, String> {
+        self.perform_query(app, &BillingEntity::personal(), &UsageQuery::default()).await
+    }
+
+    /// Extract usage of `entity` for `query` in the hidden webview
+    pub async fn perform_query(
+        &mut self,
+        app: &AppHandle,
+        entity: &BillingEntity,
+        query: &UsageQuery,
+    ) -> Result<ExtractionResult, String> {
+        let _session = HIDDEN_WEBVIEW_SESSION.lock().await;
+
         // Create event channel
         let (tx, mut rx) = mpsc::channel::<HiddenWebviewEvent>(10);
         
@@ -881,21 +904,39 @@ impl AuthManager {
         }
 
         // Create hidden webview
-        let window = self.create_hidden_webview(app)?;
+        let window = self.create_hidden_webview(app, entity, query)?;
 
         // Wait for extraction events
         let timeout = tokio::time::timeout(Duration::from_secs(EXTRACTION_TIMEOUT_SECS), async {
             let mut customer_id: Option<u64> = None;
             let mut usage_data: Option<UsageData> = None;
             let mut usage_history: Option<Vec<UsageHistoryRow>> = None;
+            let mut usage_models: Option<Vec<UsageModelRow>> = None;
             let mut error: Option<String> = None;
+            let mut schema_drift: Option<SchemaDriftError> = None;
+            let mut extractor_version: Option<u32> = None;
+            let mut billing_entities: Option<Vec<BillingEntity>> = None;
+            let mut session_expired = false;
 
             while let Some(event) = rx.recv().await {
                 log::info!("Received hidden webview event: {}", event.event);
                 
                 match event.event.as_str() {
+                    "auth:extraction:page" => {
+                        let url = serde_json::from_str::<serde_json::Value>(&event.payload)
+                            .ok()
+                            .and_then(|page| page.get("url").and_then(|v| v.as_str()).map(str::to_string))
+                            .unwrap_or_default();
+                        if is_session_expired_url(&url) {
+                            log::warn!("GitHub session expired: hidden webview landed on {}", url);
+                            session_expired = true;
+                            error = Some("GitHub session expired".to_string());
+                            break;
+                        }
+                    }
                     "auth:extraction:customer" => {
                         if let Ok(result) = serde_json::from_str::<serde_json::Value>(&event.payload) {
+                            extractor_version = extractor::reported_version(&result);
                             if result.get("success").and_then(|v| v.as_bool()).unwrap_or(false) {
                                 customer_id = result.get("id").and_then(|v| v.as_u64());
                             } else {
@@ -907,28 +948,29 @@ impl AuthManager {
                         if let Ok(result) = serde_json::from_str::<serde_json::Value>(&event.payload) {
                             // Parse usage card
                             if let Some(usage_card) = result.get("usageCard").and_then(|v| v.get("data")) {
-                                usage_data = Some(UsageData {
-                                    net_billed_amount: usage_card.get("netBilledAmount").and_then(|v| v.as_f64()).unwrap_or(0.0),
-                                    net_quantity: usage_card.get("netQuantity").and_then(|v| v.as_u64()).unwrap_or(0),
-                                    discount_quantity: usage_card.get("discountQuantity").and_then(|v| v.as_u64()).unwrap_or(0),
-                                    user_premium_request_entitlement: usage_card.get("userPremiumRequestEntitlement").and_then(|v| v.as_u64()).unwrap_or(0),
-                                    filtered_user_premium_request_entitlement: usage_card.get("filteredUserPremiumRequestEntitlement").and_then(|v| v.as_u64()).unwrap_or(0),
-                                });
+                                usage_data = Some(parse_usage_card(usage_card));
                             }
 
                             // Parse usage table
-                            if let Some(rows) = result
-                                .get("usageTable")
-                                .and_then(|v| v.get("data"))
-                                .and_then(|v| v.get("table"))
-                                .and_then(|v| v.get("rows"))
-                                .and_then(|v| v.as_array()) 
-                            {
-                                let history: Vec<UsageHistoryRow> = rows.iter().filter_map(parse_usage_history_row).collect();
-                                usage_history = Some(history);
+                            if let Some(usage_table) = result.get("usageTable") {
+                                let pages = usage_table_pages(usage_table);
+                                let parsed = match query.group {
+                                    UsageGrouping::Day => parse_usage_table_pages(&pages)
+                                        .map(|history| usage_history = history),
+                                    UsageGrouping::Model => parse_usage_model_table_pages(&pages)
+                                        .map(|models| usage_models = models),
+                                };
+                                if let Err(drift) = parsed {
+                                    schema_drift = Some(drift);
+                                }
                             }
                         }
                     }
+                    "auth:extraction:entities" => {
+                        if let Ok(result) = serde_json::from_str::<serde_json::Value>(&event.payload) {
+                            billing_entities = parse_billing_entities(&result);
+                        }
+                    }
                     "auth:extraction:complete" => {
                         // Extraction is complete, break the loop
                         break;
@@ -941,7 +983,12 @@ impl AuthManager {
                 customer_id,
                 usage_data,
                 usage_history,
+                usage_models,
                 error,
+                schema_drift,
+                extractor_version,
+                billing_entities,
+                session_expired,
             }
         }).await;
 
@@ -960,7 +1007,12 @@ impl AuthManager {
                 customer_id: None,
                 usage_data: None,
                 usage_history: None,
+                usage_models: None,
                 error: Some("Extraction timed out".to_string()),
+                schema_drift: None,
+                extractor_version: None,
+                billing_entities: None,
+                session_expired: false,
             }),
         }
     }
@@ -977,12 +1029,14 @@ impl AuthManager {
         self.customer_id.is_some()
     }
 
-    /// Fetch updates via webview using a simpler approach
+    /// Fetch the GitHub releases listing via webview using a simpler approach
     /// This creates a temporary webview that navigates and injects JavaScript
     pub async fn fetch_github_releases(
         &mut self,
         app: &AppHandle,
     ) -> Result<serde_json::Value, String> {
+        let _session = HIDDEN_WEBVIEW_SESSION.lock().await;
+
         // Create event channel
         let (tx, mut rx) = mpsc::channel::<HiddenWebviewEvent>(10);
 
@@ -1038,7 +1092,7 @@ impl AuthManager {
                     await sendResult('update_check:error', {{ success: false, error: error.message || error.toString() }});
                 }}
             }})()
-        "#, GITHUB_API_URL);
+        "#, GITHUB_RELEASES_API_URL);
 
         // Create minimal hidden webview
         let window = WebviewWindowBuilder::new(
@@ -1115,3 +1169,300 @@ pub async fn hidden_webview_event(event: String, payload: String) -> Result<(),
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_sign_in_pages() {
+        for url in [
+            "https://github.com/login?return_to=https%3A%2F%2Fgithub.com%2Fsettings%2Fbilling",
+            "https://github.com/session",
+            "https://github.com/sessions/two-factor/app",
+            "https://github.com/sessions/verified-device",
+            "https://github.com/login/",
+        ] {
+            assert!(is_session_expired_url(url), "{}", url);
+        }
+        for url in [
+            "https://github.com/settings/billing",
+            "https://github.com/organizations/acme/settings/billing",
+            "https://github.com/loginform",
+            "https://example.com/login",
+            "not a url",
+        ] {
+            assert!(!is_session_expired_url(url), "{}", url);
+        }
+    }
+
+    fn fixture(json: &str) -> serde_json::Value {
+        serde_json::from_str(json).expect("fixture is valid JSON")
+    }
+
+    fn parse_usage_table(table: &serde_json::Value) -> Result<Option<Vec<UsageHistoryRow>>, SchemaDriftError> {
+        parse_usage_table_pages(&[table])
+    }
+
+    fn table_fixture(json: &str) -> Vec<UsageHistoryRow> {
+        parse_usage_table(&fixture(json))
+            .expect("fixture matches the expected columns")
+            .expect("fixture has table rows")
+    }
+
+    #[test]
+    fn parses_usage_table() {
+        let rows = table_fixture(include_str!("../tests/fixtures/copilot_usage_table.json"));
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].date, "2026-02-03 00:00:00 +0000 UTC");
+        assert_eq!(rows[0].included_requests, 42);
+        assert_eq!(rows[0].billed_requests, 0);
+        assert_eq!(rows[0].gross_amount, 1.68);
+        assert_eq!(rows[0].billed_amount, 0.0);
+        assert_eq!(rows[0].currency.as_deref(), Some("USD"));
+
+        let models: Vec<&str> = rows[0].models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(models, ["Claude Sonnet 4", "GPT-4.1"]);
+        assert_eq!(rows[0].models[1].included_requests, 12);
+        assert_eq!(rows[0].models[1].gross_amount, 0.48);
+
+        assert_eq!(rows[1].billed_requests, 5);
+        assert_eq!(rows[1].billed_amount, 0.2);
+        assert!(rows[2].models.is_empty());
+    }
+
+    #[test]
+    fn usage_table_edge_cases() {
+        let rows = table_fixture(include_str!(
+            "../tests/fixtures/copilot_usage_table_edge_cases.json"
+        ));
+        let dates: Vec<&str> = rows.iter().map(|r| r.date.as_str()).collect();
+
+        // Dropped: a row with only four cells and the id-less total row
+        assert_eq!(
+            dates,
+            [
+                "2026-03-05 00:00:00 +0000 UTC",
+                "2026-03-04 00:00:00 +0000 UTC",
+                "2026-03-03 00:00:00 +0000 UTC",
+                "2026-03-02 00:00:00 +0000 UTC"
+            ]
+        );
+
+        // Thousands separators
+        assert_eq!(rows[0].included_requests, 1204);
+        assert_eq!(rows[0].gross_amount, 48.16);
+        assert_eq!(rows[0].currency.as_deref(), Some("USD"));
+
+        // Credits with the sign on either side of the currency symbol
+        assert_eq!(rows[1].billed_amount, -0.08);
+        assert_eq!(rows[2].billed_amount, -0.04);
+        assert!(rows[2].models.is_empty());
+
+        // Extra trailing columns are ignored; short model rows are skipped
+        assert_eq!(rows[3].included_requests, 7);
+        assert_eq!(rows[3].billed_amount, 0.04);
+        let models: Vec<(&str, u32)> = rows[3]
+            .models
+            .iter()
+            .map(|m| (m.name.as_str(), m.included_requests))
+            .collect();
+        assert_eq!(models, [("GPT-4.1", 7), ("o3", 1000)]);
+    }
+
+    #[test]
+    fn usage_table_in_euros() {
+        let rows = table_fixture(include_str!("../tests/fixtures/copilot_usage_table_eur.json"));
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].included_requests, 1250);
+        assert_eq!(rows[0].gross_amount, 1004.8);
+        assert_eq!(rows[0].billed_amount, -0.48);
+        assert_eq!(rows[0].currency.as_deref(), Some("EUR"));
+        assert_eq!(rows[0].models[0].gross_amount, 1004.8);
+        assert_eq!(rows[1].gross_amount, 0.12);
+        assert_eq!(rows[1].currency.as_deref(), Some("EUR"));
+    }
+
+    #[test]
+    fn usage_table_without_rows() {
+        assert!(parse_usage_table(&serde_json::json!({ "table": {} })).unwrap().is_none());
+        assert!(parse_usage_table(&serde_json::json!({ "table": { "rows": [] } }))
+            .unwrap()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn merges_usage_table_pages() {
+        let usage_table = fixture(include_str!("../tests/fixtures/usage_table_pages.json"));
+        let rows = parse_usage_table_pages(&usage_table_pages(&usage_table))
+            .unwrap()
+            .unwrap();
+
+        // Feb 2 straddles both pages; page 2 has no headers and reuses page 1's columns
+        let dates: Vec<&str> = rows.iter().map(|r| &r.date[..10]).collect();
+        assert_eq!(dates, ["2026-02-03", "2026-02-02", "2026-02-01"]);
+        assert_eq!(rows[1].included_requests, 50);
+        let models: Vec<&str> = rows[1].models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(models, ["Claude Sonnet 4", "GPT-4.1"]);
+        assert_eq!(rows[2].included_requests, 1);
+        assert_eq!(rows[2].gross_amount, 0.04);
+    }
+
+    #[test]
+    fn parses_usage_table_grouped_by_model() {
+        let usage_table = fixture(include_str!("../tests/fixtures/usage_table_by_model.json"));
+        let models = parse_usage_model_table_pages(&usage_table_pages(&usage_table))
+            .unwrap()
+            .unwrap();
+
+        // GPT-4.1 is repeated at the top of page 2
+        let names: Vec<&str> = models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Claude Sonnet 4", "GPT-4.1", "o3"]);
+        assert_eq!(models[1].billed_requests, 12);
+        assert_eq!(models[1].billed_amount, 0.48);
+        assert_eq!(models[2].included_requests, 1000);
+    }
+
+    #[test]
+    fn reads_unpaginated_usage_table_results() {
+        let mut usage_table = fixture(include_str!("../tests/fixtures/usage_table_pages.json"));
+        usage_table.as_object_mut().unwrap().remove("pages");
+
+        let rows = parse_usage_table_pages(&usage_table_pages(&usage_table))
+            .unwrap()
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let failed = serde_json::json!({ "success": false, "error": "Usage table request failed: 500" });
+        assert!(usage_table_pages(&failed).is_empty());
+        assert!(parse_usage_table_pages(&usage_table_pages(&failed)).unwrap().is_none());
+    }
+
+    #[test]
+    fn maps_columns_by_header() {
+        let rows = table_fixture(include_str!(
+            "../tests/fixtures/copilot_usage_table_reordered.json"
+        ));
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].included_requests, 50);
+        assert_eq!(rows[0].billed_requests, 10);
+        assert_eq!(rows[0].gross_amount, 2.4);
+        assert_eq!(rows[0].billed_amount, 0.4);
+
+        // The subtable has its own header order
+        let model = &rows[0].models[0];
+        assert_eq!(model.name, "Claude Opus 4");
+        assert_eq!(model.included_requests, 50);
+        assert_eq!(model.billed_requests, 10);
+        assert_eq!(model.gross_amount, 2.4);
+        assert_eq!(model.billed_amount, 0.4);
+
+        assert_eq!(rows[1].included_requests, 20);
+        assert_eq!(rows[1].gross_amount, 0.8);
+    }
+
+    #[test]
+    fn reports_missing_columns() {
+        let drift = parse_usage_table(&fixture(include_str!(
+            "../tests/fixtures/copilot_usage_table_drift.json"
+        )))
+        .unwrap_err();
+
+        assert_eq!(drift.table, "usage");
+        assert_eq!(drift.missing_columns, ["grossAmount"]);
+        assert_eq!(
+            drift.headers,
+            ["Date", "Included requests", "Billed requests", "List price", "Billed amount"]
+        );
+    }
+
+    #[test]
+    fn reports_missing_subtable_columns() {
+        let table = serde_json::json!({
+            "table": {
+                "rows": [{
+                    "id": "2026-04-01 00:00:00 +0000 UTC",
+                    "cells": [
+                        { "value": "Apr 1" },
+                        { "value": "20" },
+                        { "value": "0" },
+                        { "value": "$0.80" },
+                        { "value": "$0.00" }
+                    ],
+                    "subtable": {
+                        "headers": ["Model", "Requests", "Gross amount"],
+                        "rows": []
+                    }
+                }]
+            }
+        });
+
+        let drift = parse_usage_table(&table).unwrap_err();
+        assert_eq!(drift.table, "model");
+        assert_eq!(
+            drift.missing_columns,
+            ["includedRequests", "billedRequests", "billedAmount"]
+        );
+    }
+
+    #[test]
+    fn identifies_columns() {
+        assert_eq!(UsageColumn::identify("Included requests"), Some(UsageColumn::IncludedRequests));
+        assert_eq!(UsageColumn::identify("billed_requests"), Some(UsageColumn::BilledRequests));
+        assert_eq!(UsageColumn::identify("billedAmount"), Some(UsageColumn::BilledAmount));
+        assert_eq!(UsageColumn::identify("Billed amount ($)"), Some(UsageColumn::BilledAmount));
+        assert_eq!(UsageColumn::identify("Gross amount"), Some(UsageColumn::GrossAmount));
+        assert_eq!(UsageColumn::identify("Model"), Some(UsageColumn::Label));
+        assert_eq!(UsageColumn::identify("Plan"), None);
+    }
+
+    #[test]
+    fn parses_billing_entities() {
+        let payload = serde_json::json!({
+            "success": true,
+            "organizations": [{ "login": "acme" }, { "id": 3 }],
+            "enterprises": [{ "slug": "acme-corp" }],
+            "extractorVersion": 2
+        });
+        let keys: Vec<String> = parse_billing_entities(&payload)
+            .unwrap()
+            .iter()
+            .map(BillingEntity::key)
+            .collect();
+        assert_eq!(keys, ["organization/acme", "enterprise/acme-corp"]);
+
+        let failed = serde_json::json!({ "success": false, "error": "offline" });
+        assert!(parse_billing_entities(&failed).is_none());
+    }
+
+    #[test]
+    fn parses_usage_card() {
+        let usage = parse_usage_card(&fixture(include_str!(
+            "../tests/fixtures/copilot_usage_card.json"
+        )));
+
+        assert_eq!(usage.net_billed_amount, 0.2);
+        assert_eq!(usage.net_quantity, 5);
+        assert_eq!(usage.discount_quantity, 342);
+        assert_eq!(usage.user_premium_request_entitlement, 300);
+        assert_eq!(usage.filtered_user_premium_request_entitlement, 300);
+    }
+
+    #[test]
+    fn usage_card_missing_fields_read_as_zero() {
+        let usage = parse_usage_card(&fixture(include_str!(
+            "../tests/fixtures/copilot_usage_card_partial.json"
+        )));
+
+        assert_eq!(usage.net_billed_amount, -1.5);
+        assert_eq!(usage.net_quantity, 0);
+        // Quantities sent as floats are not accepted
+        assert_eq!(usage.discount_quantity, 0);
+        assert_eq!(usage.user_premium_request_entitlement, 1500);
+        assert_eq!(usage.filtered_user_premium_request_entitlement, 0);
+    }
+}
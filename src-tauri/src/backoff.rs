@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Consecutive failed polls after which the circuit opens
+pub const FAILURE_THRESHOLD: u32 = 5;
+/// Longest wait between polls while backing off
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+/// Wait before the trial poll of an open circuit
+pub const OPEN_COOLDOWN: Duration = Duration::from_secs(60 * 60);
+/// Delays vary by up to this fraction either way, so retries do not line up
+const JITTER: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    /// Polling at the refresh interval
+    Closed,
+    /// Recent polls failed; retrying with growing delays
+    BackingOff,
+    /// Too many polls failed; only a trial poll after the cooldown
+    Open,
+}
+
+/// Consecutive failures of background polling and the delay they call for
+#[derive(Debug, Clone, Default)]
+pub struct PollBackoff {
+    failures: u32,
+}
+
+impl PollBackoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match self.failures {
+            0 => CircuitState::Closed,
+            n if n < FAILURE_THRESHOLD => CircuitState::BackingOff,
+            _ => CircuitState::Open,
+        }
+    }
+
+    /// Returns whether the state changed
+    pub fn record_success(&mut self) -> bool {
+        let changed = self.failures > 0;
+        self.failures = 0;
+        changed
+    }
+
+    /// Returns whether the state changed
+    pub fn record_failure(&mut self) -> bool {
+        let before = self.state();
+        self.failures = self.failures.saturating_add(1);
+        self.state() != before
+    }
+
+    /// Delay before the next poll, never shorter than `interval`
+    /// `jitter` in [0, 1) spreads failed-poll delays by up to ±20%; a healthy circuit
+    /// keeps the exact interval
+    pub fn next_delay(&self, interval: Duration, jitter: f64) -> Duration {
+        let delay = match self.state() {
+            CircuitState::Closed => return interval,
+            CircuitState::BackingOff => {
+                let factor = 2u32.saturating_pow(self.failures);
+                interval.saturating_mul(factor).min(MAX_BACKOFF)
+            }
+            CircuitState::Open => OPEN_COOLDOWN,
+        };
+        delay
+            .mul_f64(1.0 + JITTER * (2.0 * jitter.clamp(0.0, 1.0) - 1.0))
+            .max(interval)
+    }
+}
+
+/// Random number in [0, 1) for jitter, from the std hasher's random keys
+pub fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Circuit state of background polling, for the tray and frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PollingStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Unix time of the next background poll, when one is scheduled
+    pub next_poll_at: Option<i64>,
+}
+
+/// Background polling health shared by the polling task and manual refreshes
+/// Kept across polling restarts, so changing the interval does not reset a backoff
+pub struct PollingHealth {
+    backoff: Mutex<PollBackoff>,
+    next_poll_at: Mutex<Option<i64>>,
+    /// Wakes the polling task when a manual refresh closes the circuit
+    reset: Notify,
+}
+
+impl Default for PollingHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PollingHealth {
+    pub fn new() -> Self {
+        Self {
+            backoff: Mutex::new(PollBackoff::new()),
+            next_poll_at: Mutex::new(None),
+            reset: Notify::new(),
+        }
+    }
+
+    pub fn status(&self) -> PollingStatus {
+        let backoff = self.backoff.lock().unwrap();
+        PollingStatus {
+            state: backoff.state(),
+            consecutive_failures: backoff.failures(),
+            next_poll_at: *self.next_poll_at.lock().unwrap(),
+        }
+    }
+
+    /// Returns whether the state changed; a change wakes the polling task
+    pub fn record_success(&self) -> bool {
+        let changed = self.backoff.lock().unwrap().record_success();
+        if changed {
+            self.reset.notify_waiters();
+        }
+        changed
+    }
+
+    /// Returns whether the state changed
+    pub fn record_failure(&self) -> bool {
+        self.backoff.lock().unwrap().record_failure()
+    }
+
+    /// Jittered delay before the next poll
+    pub fn next_delay(&self, interval: Duration) -> Duration {
+        self.backoff.lock().unwrap().next_delay(interval, jitter())
+    }
+
+    pub fn set_next_poll_at(&self, timestamp: Option<i64>) {
+        *self.next_poll_at.lock().unwrap() = timestamp;
+    }
+
+    /// Resolves when a manual refresh closes the circuit
+    pub async fn reset(&self) {
+        self.reset.notified().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn backs_off_then_opens() {
+        let mut backoff = PollBackoff::new();
+        assert_eq!(backoff.next_delay(MINUTE, 0.9), MINUTE);
+
+        let delays: Vec<u64> = (0..FAILURE_THRESHOLD)
+            .map(|_| {
+                backoff.record_failure();
+                backoff.next_delay(MINUTE, 0.5).as_secs()
+            })
+            .collect();
+        assert_eq!(delays, [120, 240, 480, 960, 3600]);
+        assert_eq!(backoff.state(), CircuitState::Open);
+
+        backoff.record_failure();
+        assert_eq!(backoff.next_delay(MINUTE, 0.5), OPEN_COOLDOWN);
+    }
+
+    #[test]
+    fn caps_and_jitters_delays() {
+        let mut backoff = PollBackoff::new();
+        for _ in 0..4 {
+            backoff.record_failure();
+        }
+        assert_eq!(backoff.next_delay(5 * MINUTE, 0.5), MAX_BACKOFF);
+        assert_eq!(backoff.next_delay(5 * MINUTE, 0.0), MAX_BACKOFF.mul_f64(0.8));
+        assert_eq!(backoff.next_delay(5 * MINUTE, 1.0), MAX_BACKOFF.mul_f64(1.2));
+        assert_eq!(backoff.next_delay(2 * OPEN_COOLDOWN, 0.5), 2 * OPEN_COOLDOWN);
+
+        let j = jitter();
+        assert!((0.0..1.0).contains(&j));
+    }
+
+    #[test]
+    fn jitter_never_polls_sooner_than_the_interval() {
+        let interval = 2 * OPEN_COOLDOWN;
+        let mut backoff = PollBackoff::new();
+        for _ in 0..=FAILURE_THRESHOLD {
+            backoff.record_failure();
+            assert!(backoff.next_delay(interval, 0.0) >= interval, "{:?}", backoff.state());
+        }
+    }
+
+    #[test]
+    fn success_closes_the_circuit() {
+        let mut backoff = PollBackoff::new();
+        assert!(backoff.record_failure());
+        assert!(!backoff.record_failure());
+        for _ in 2..FAILURE_THRESHOLD - 1 {
+            backoff.record_failure();
+        }
+        assert!(backoff.record_failure());
+        assert_eq!(backoff.state(), CircuitState::Open);
+
+        assert!(backoff.record_success());
+        assert_eq!(backoff.state(), CircuitState::Closed);
+        assert!(!backoff.record_success());
+    }
+}
@@ -0,0 +1,294 @@
+use chrono::{DateTime, Datelike, FixedOffset, Months, NaiveDate, Utc};
+
+use crate::calendar::{self, Clock};
+use crate::store::AppSettings;
+
+/// Parse a billing time zone setting: "UTC", "local" or a fixed offset like "+08:00"
+/// "local" resolves to the system offset at `now`
+pub fn parse_time_zone(time_zone: &str, now: DateTime<Utc>) -> Option<FixedOffset> {
+    let tz = time_zone.trim();
+    if tz.is_empty() || tz.eq_ignore_ascii_case("utc") || tz.eq_ignore_ascii_case("z") {
+        return FixedOffset::east_opt(0);
+    }
+    if tz.eq_ignore_ascii_case("local") {
+        return Some(*now.with_timezone(&chrono::Local).offset());
+    }
+
+    let (sign, rest) = match tz.as_bytes()[0] {
+        b'+' => (1, &tz[1..]),
+        b'-' => (-1, &tz[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h, m),
+        // Byte length, so slice with `get` in case the digits are not ASCII
+        None if rest.len() == 4 => (rest.get(..2)?, rest.get(2..)?),
+        None => (rest, "0"),
+    };
+    let hours = offset_field(hours)?;
+    let minutes = offset_field(minutes)?;
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Hours or minutes of an offset: one or two ASCII digits, so signs and spaces that
+/// `str::parse` would accept are refused
+fn offset_field(field: &str) -> Option<i32> {
+    if field.is_empty() || field.len() > 2 || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    field.parse().ok()
+}
+
+/// Whether `time_zone` is a value `parse_time_zone` accepts
+pub fn is_valid_time_zone(time_zone: &str) -> bool {
+    parse_time_zone(time_zone, Utc::now()).is_some()
+}
+
+/// Date of `anchor_day` in the given month, clamped to the month's last day
+fn anchor_date(year: i32, month: u32, anchor_day: u32) -> NaiveDate {
+    let day = anchor_day.clamp(1, calendar::days_in_month(year, month));
+    NaiveDate::from_ymd_opt(year, month, day).expect("clamped day is valid")
+}
+
+/// One quota period: from the anchor day at 00:00 in the reset time zone until the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BillingCycle {
+    /// First day of the cycle (in the reset time zone)
+    pub start_date: NaiveDate,
+    /// First day of the next cycle (in the reset time zone)
+    pub end_date: NaiveDate,
+    /// Offset the cycle boundaries are computed in
+    pub offset: FixedOffset,
+    /// Configured anchor day (1-31), before clamping to short months
+    pub anchor_day: u32,
+}
+
+impl BillingCycle {
+    /// Cycle containing `now` for the given anchor day (1-31) and reset offset
+    pub fn containing(now: DateTime<Utc>, anchor_day: u32, offset: FixedOffset) -> Self {
+        let today = now.with_timezone(&offset).date_naive();
+
+        let this_month = anchor_date(today.year(), today.month(), anchor_day);
+        let start_date = if today >= this_month {
+            this_month
+        } else {
+            let previous = today.with_day(1).expect("valid date") - Months::new(1);
+            anchor_date(previous.year(), previous.month(), anchor_day)
+        };
+        let next = start_date.with_day(1).expect("valid date") + Months::new(1);
+        let end_date = anchor_date(next.year(), next.month(), anchor_day);
+
+        Self {
+            start_date,
+            end_date,
+            offset,
+            anchor_day,
+        }
+    }
+
+    /// Cycle containing `now` according to the billing settings
+    /// An invalid time zone setting falls back to UTC
+    pub fn from_settings(settings: &AppSettings, now: DateTime<Utc>) -> Self {
+        let offset = parse_time_zone(&settings.billing_time_zone, now).unwrap_or_else(|| {
+            log::warn!(
+                "Invalid billing time zone {:?}, using UTC",
+                settings.billing_time_zone
+            );
+            FixedOffset::east_opt(0).unwrap()
+        });
+        Self::containing(now, settings.billing_cycle_anchor_day, offset)
+    }
+
+    /// UTC calendar month containing `now`
+    /// GitHub's billing usage endpoints report by calendar month, whatever the anchor day
+    pub fn calendar_month(now: DateTime<Utc>) -> Self {
+        Self::containing(now, 1, FixedOffset::east_opt(0).unwrap())
+    }
+
+    /// Current cycle according to the billing settings
+    pub fn current(settings: &AppSettings, clock: &dyn Clock) -> Self {
+        Self::from_settings(settings, clock.now())
+    }
+
+    /// The cycle before this one
+    pub fn previous(&self) -> Self {
+        Self::containing(
+            self.start() - chrono::Duration::seconds(1),
+            self.anchor_day,
+            self.offset,
+        )
+    }
+
+    /// Stable key for the cycle, used to scope per-cycle state (e.g. "2026-02")
+    /// Cycles are monthly, so the month they start in identifies them
+    pub fn key(&self) -> String {
+        self.start_date.format("%Y-%m").to_string()
+    }
+
+    /// Instant the cycle starts
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start_date
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(self.offset)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    /// Instant the quota resets (start of the next cycle)
+    pub fn end(&self) -> DateTime<Utc> {
+        self.end_date
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(self.offset)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    /// Calendar date of `now` in the reset time zone
+    pub fn date_of(&self, now: DateTime<Utc>) -> NaiveDate {
+        now.with_timezone(&self.offset).date_naive()
+    }
+
+    /// Number of days in the cycle
+    pub fn total_days(&self) -> u32 {
+        (self.end_date - self.start_date).num_days() as u32
+    }
+
+    /// 1-based day of the cycle `now` falls on (1 on the anchor day)
+    pub fn day_of_cycle(&self, now: DateTime<Utc>) -> u32 {
+        let days = (self.date_of(now) - self.start_date).num_days() + 1;
+        days.clamp(1, self.total_days() as i64) as u32
+    }
+
+    /// Whole days left in the cycle after the day `now` falls on
+    pub fn remaining_days(&self, now: DateTime<Utc>) -> u32 {
+        self.total_days().saturating_sub(self.day_of_cycle(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc() -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn december_cycle_rolls_into_next_year() {
+        let now = Utc.with_ymd_and_hms(2025, 12, 31, 23, 0, 0).unwrap();
+        let cycle = BillingCycle::containing(now, 1, utc());
+
+        assert_eq!(cycle.start_date, date(2025, 12, 1));
+        assert_eq!(cycle.end_date, date(2026, 1, 1));
+        assert_eq!(cycle.total_days(), 31);
+        assert_eq!(cycle.day_of_cycle(now), 31);
+        assert_eq!(cycle.remaining_days(now), 0);
+    }
+
+    #[test]
+    fn february_in_leap_and_common_years() {
+        let leap = BillingCycle::containing(
+            Utc.with_ymd_and_hms(2024, 2, 10, 0, 0, 0).unwrap(),
+            1,
+            utc(),
+        );
+        let common = BillingCycle::containing(
+            Utc.with_ymd_and_hms(2026, 2, 10, 0, 0, 0).unwrap(),
+            1,
+            utc(),
+        );
+
+        assert_eq!(leap.total_days(), 29);
+        assert_eq!(common.total_days(), 28);
+    }
+
+    #[test]
+    fn anchor_day_is_clamped_to_short_months() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 5, 0, 0, 0).unwrap();
+        let cycle = BillingCycle::containing(now, 31, utc());
+
+        assert_eq!(cycle.start_date, date(2026, 2, 28));
+        assert_eq!(cycle.end_date, date(2026, 3, 31));
+        assert_eq!(cycle.day_of_cycle(now), 6);
+    }
+
+    #[test]
+    fn mid_month_anchor_spans_year_boundary() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 10, 0, 0, 0).unwrap();
+        let cycle = BillingCycle::containing(now, 15, utc());
+
+        assert_eq!(cycle.start_date, date(2025, 12, 15));
+        assert_eq!(cycle.end_date, date(2026, 1, 15));
+        assert_eq!(cycle.key(), "2025-12");
+        assert_eq!(cycle.day_of_cycle(now), 27);
+        assert_eq!(cycle.remaining_days(now), 4);
+    }
+
+    #[test]
+    fn previous_cycle_keeps_the_anchor_day() {
+        let now = Utc.with_ymd_and_hms(2026, 4, 5, 0, 0, 0).unwrap();
+        let cycle = BillingCycle::containing(now, 31, utc());
+        assert_eq!(cycle.start_date, date(2026, 3, 31));
+
+        let previous = cycle.previous();
+        assert_eq!(previous.start_date, date(2026, 2, 28));
+        assert_eq!(previous.end_date, date(2026, 3, 31));
+        assert_eq!(previous.previous().start_date, date(2026, 1, 31));
+    }
+
+    #[test]
+    fn cycle_boundaries_follow_the_reset_time_zone() {
+        let plus8 = parse_time_zone("+08:00", Utc::now()).unwrap();
+        // 17:00 UTC on 28 Feb is already 1 Mar in UTC+8
+        let now = Utc.with_ymd_and_hms(2026, 2, 28, 17, 0, 0).unwrap();
+
+        let local = BillingCycle::containing(now, 1, plus8);
+        assert_eq!(local.start_date, date(2026, 3, 1));
+        assert_eq!(
+            local.start(),
+            Utc.with_ymd_and_hms(2026, 2, 28, 16, 0, 0).unwrap()
+        );
+
+        let utc_cycle = BillingCycle::containing(now, 1, utc());
+        assert_eq!(utc_cycle.start_date, date(2026, 2, 1));
+        assert_eq!(
+            utc_cycle.end(),
+            Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_time_zones() {
+        let now = Utc::now();
+        assert_eq!(parse_time_zone("UTC", now), FixedOffset::east_opt(0));
+        assert_eq!(
+            parse_time_zone("+08:00", now),
+            FixedOffset::east_opt(8 * 3600)
+        );
+        assert_eq!(
+            parse_time_zone("-0530", now),
+            FixedOffset::west_opt(5 * 3600 + 1800)
+        );
+        assert_eq!(parse_time_zone("+9", now), FixedOffset::east_opt(9 * 3600));
+        assert!(parse_time_zone("local", now).is_some());
+        assert!(parse_time_zone("Asia/Singapore", now).is_none());
+        assert!(parse_time_zone("+15:00", now).is_none());
+        assert!(parse_time_zone("+a€", now).is_none());
+        assert!(parse_time_zone("-€0", now).is_none());
+        assert!(parse_time_zone("+-5", now).is_none());
+        assert!(parse_time_zone("-08:-30", now).is_none());
+        assert!(parse_time_zone("+08:+30", now).is_none());
+        assert!(parse_time_zone("+008:00", now).is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+/// Key of the signed-in user's own billing
+pub const PERSONAL_ENTITY_KEY: &str = "personal";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BillingEntityKind {
+    Personal,
+    Organization,
+    Enterprise,
+}
+
+/// Someone whose Copilot bill the user can see: themselves, an organization or an enterprise
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingEntity {
+    pub kind: BillingEntityKind,
+    /// Organization login or enterprise slug; empty for the personal account
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl BillingEntity {
+    pub fn personal() -> Self {
+        Self {
+            kind: BillingEntityKind::Personal,
+            slug: String::new(),
+            name: None,
+        }
+    }
+
+    pub fn organization(login: &str, name: Option<String>) -> Self {
+        Self {
+            kind: BillingEntityKind::Organization,
+            slug: login.to_string(),
+            name,
+        }
+    }
+
+    pub fn enterprise(slug: &str, name: Option<String>) -> Self {
+        Self {
+            kind: BillingEntityKind::Enterprise,
+            slug: slug.to_string(),
+            name,
+        }
+    }
+
+    pub fn is_personal(&self) -> bool {
+        self.kind == BillingEntityKind::Personal
+    }
+
+    /// Stable key for settings and caches: "personal", "organization/<login>" or "enterprise/<slug>"
+    pub fn key(&self) -> String {
+        match self.kind {
+            BillingEntityKind::Personal => PERSONAL_ENTITY_KEY.to_string(),
+            BillingEntityKind::Organization => format!("organization/{}", self.slug),
+            BillingEntityKind::Enterprise => format!("enterprise/{}", self.slug),
+        }
+    }
+
+    /// Prefix of the entity's pages on github.com; the billing settings live under
+    /// `<prefix>/settings/billing`
+    pub fn path_prefix(&self) -> String {
+        match self.kind {
+            BillingEntityKind::Personal => String::new(),
+            BillingEntityKind::Organization => format!("/organizations/{}", self.slug),
+            BillingEntityKind::Enterprise => format!("/enterprises/{}", self.slug),
+        }
+    }
+
+    /// Name shown in menus
+    pub fn label(&self) -> String {
+        match (&self.name, self.kind) {
+            (Some(name), _) if !name.is_empty() => name.clone(),
+            (_, BillingEntityKind::Personal) => "Personal".to_string(),
+            _ => self.slug.clone(),
+        }
+    }
+}
+
+/// Known entities after a discovery: the personal account first, then `discovered`
+/// Entities no longer discovered are dropped unless they are selected, so a failed or
+/// partial discovery never silently stops tracking one
+pub fn merge_entities(
+    known: &[BillingEntity],
+    discovered: Vec<BillingEntity>,
+    selected: &[String],
+) -> Vec<BillingEntity> {
+    let mut merged = vec![BillingEntity::personal()];
+    let kept = known
+        .iter()
+        .filter(|entity| selected.contains(&entity.key()))
+        .cloned();
+    for entity in discovered.into_iter().chain(kept) {
+        if !merged.iter().any(|m| m.key() == entity.key()) {
+            merged.push(entity);
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_and_paths() {
+        let personal = BillingEntity::personal();
+        assert_eq!(personal.key(), PERSONAL_ENTITY_KEY);
+        assert_eq!(personal.path_prefix(), "");
+        assert_eq!(personal.label(), "Personal");
+
+        let org = BillingEntity::organization("acme", None);
+        assert_eq!(org.key(), "organization/acme");
+        assert_eq!(org.path_prefix(), "/organizations/acme");
+        assert_eq!(org.label(), "acme");
+
+        let enterprise = BillingEntity::enterprise("acme-corp", Some("Acme Corp".to_string()));
+        assert_eq!(enterprise.key(), "enterprise/acme-corp");
+        assert_eq!(enterprise.path_prefix(), "/enterprises/acme-corp");
+        assert_eq!(enterprise.label(), "Acme Corp");
+    }
+
+    #[test]
+    fn merge_keeps_selected_entities() {
+        let known = vec![
+            BillingEntity::personal(),
+            BillingEntity::organization("old", None),
+            BillingEntity::enterprise("kept", None),
+        ];
+        let discovered = vec![
+            BillingEntity::organization("acme", None),
+            BillingEntity::organization("acme", None),
+        ];
+        let selected = vec!["enterprise/kept".to_string()];
+
+        let keys: Vec<String> = merge_entities(&known, discovered, &selected)
+            .iter()
+            .map(BillingEntity::key)
+            .collect();
+        assert_eq!(keys, ["personal", "organization/acme", "enterprise/kept"]);
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+
+/// Source of the current time, injectable so date math is deterministic in tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock frozen at a given instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Number of days in the given month (1-12)
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => panic!("invalid month {}", month),
+    }
+}
+
+pub fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Whether `date` is expected to see weekend-like usage (a weekend or a holiday)
+pub fn is_day_off(date: NaiveDate, holidays: &[NaiveDate]) -> bool {
+    is_weekend(date) || holidays.contains(&date)
+}
+
+/// Parse holiday dates ("YYYY-MM-DD"), skipping invalid entries
+pub fn parse_holidays(dates: &[String]) -> Vec<NaiveDate> {
+    dates
+        .iter()
+        .filter_map(|d| match NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d") {
+            Ok(date) => Some(date),
+            Err(_) => {
+                log::warn!("Ignoring invalid holiday date: {:?}", d);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Working days and days off in a date range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DayCounts {
+    pub weekdays: u32,
+    pub days_off: u32,
+}
+
+/// Count the days strictly after `after` and strictly before `before`
+/// Holidays falling on weekdays count as days off
+pub fn count_days_between(
+    after: NaiveDate,
+    before: NaiveDate,
+    holidays: &[NaiveDate],
+) -> DayCounts {
+    let mut counts = DayCounts::default();
+    let mut date = after.succ_opt();
+    while let Some(day) = date {
+        if day >= before {
+            break;
+        }
+        if is_day_off(day, holidays) {
+            counts.days_off += 1;
+        } else {
+            counts.weekdays += 1;
+        }
+        date = day.succ_opt();
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn days(weekdays: u32, days_off: u32) -> DayCounts {
+        DayCounts { weekdays, days_off }
+    }
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2026));
+    }
+
+    #[test]
+    fn days_in_each_month() {
+        let expected = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+        for (month, days) in (1..=12).zip(expected) {
+            assert_eq!(days_in_month(2026, month), days, "month {}", month);
+        }
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+    }
+
+    #[test]
+    fn december_has_31_days() {
+        assert_eq!(days_in_month(2025, 12), 31);
+        assert_eq!(days_in_month(2026, 12), 31);
+    }
+
+    #[test]
+    fn counts_days_across_year_boundary() {
+        // Tue 30 Dec 2025 -> Thu 1 Jan 2026 leaves only Wed 31 Dec in between
+        let counts = count_days_between(date(2025, 12, 30), date(2026, 1, 1), &[]);
+        assert_eq!(counts, days(1, 0));
+
+        // Sat 27 Dec 2025 .. Sat 3 Jan 2026: Sun + Mon-Fri
+        let counts = count_days_between(date(2025, 12, 27), date(2026, 1, 3), &[]);
+        assert_eq!(counts, days(5, 1));
+    }
+
+    #[test]
+    fn counts_leap_day() {
+        // Wed 28 Feb 2024 -> Fri 1 Mar 2024 leaves Thu 29 Feb
+        let counts = count_days_between(date(2024, 2, 28), date(2024, 3, 1), &[]);
+        assert_eq!(counts, days(1, 0));
+    }
+
+    #[test]
+    fn holidays_count_as_days_off() {
+        let holidays = parse_holidays(&["2025-12-25".to_string(), "2026-01-01".to_string()]);
+        let counts = count_days_between(date(2025, 12, 21), date(2026, 1, 4), &holidays);
+        // 22 Dec - 3 Jan: 10 weekdays minus 2 holidays, plus Sat/Sun 27-28 and Sat 3 Jan
+        assert_eq!(counts, days(8, 5));
+    }
+
+    #[test]
+    fn empty_range() {
+        let counts = count_days_between(date(2026, 3, 31), date(2026, 4, 1), &[]);
+        assert_eq!(counts, DayCounts::default());
+        let counts = count_days_between(date(2026, 4, 5), date(2026, 4, 1), &[]);
+        assert_eq!(counts, DayCounts::default());
+    }
+
+    #[test]
+    fn skips_invalid_holidays() {
+        let holidays = parse_holidays(&["2026-02-30".to_string(), " 2026-05-01 ".to_string()]);
+        assert_eq!(holidays, vec![date(2026, 5, 1)]);
+    }
+
+    #[test]
+    fn fixed_clock_is_frozen() {
+        let instant = date(2026, 1, 31).and_hms_opt(23, 59, 59).unwrap().and_utc();
+        let clock = FixedClock(instant);
+        assert_eq!(clock.now(), instant);
+        assert_eq!(clock.now(), clock.now());
+    }
+}
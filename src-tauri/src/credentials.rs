@@ -0,0 +1,280 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+const KEYRING_SERVICE: &str = "copilot-tracker";
+const KEYRING_USER: &str = "github-token";
+const CREDENTIALS_FILENAME: &str = "credentials.bin";
+const CREDENTIALS_KEY_FILENAME: &str = "credentials.key";
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// Somewhere a single secret can be kept
+pub trait CredentialStore: Send + Sync {
+    /// Short name for logs and the settings UI
+    fn name(&self) -> &'static str;
+    fn get(&self) -> Result<Option<String>, String>;
+    fn set(&self, secret: &str) -> Result<(), String>;
+    /// Remove the secret; succeeds when there is none
+    fn delete(&self) -> Result<(), String>;
+}
+
+/// The OS keychain: Keychain on macOS, Credential Manager on Windows, Secret Service on Linux
+pub struct KeyringStore {
+    entry: keyring::Entry,
+}
+
+impl KeyringStore {
+    /// `None` when no keychain is reachable, e.g. on headless Linux without a Secret Service
+    pub fn open() -> Option<Self> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .map_err(|e| log::warn!("[Credentials] OS keychain unavailable: {}", e))
+            .ok()?;
+        match entry.get_password() {
+            Ok(_) | Err(keyring::Error::NoEntry) => Some(Self { entry }),
+            Err(e) => {
+                log::warn!("[Credentials] OS keychain unavailable: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn name(&self) -> &'static str {
+        "keychain"
+    }
+
+    fn get(&self) -> Result<Option<String>, String> {
+        match self.entry.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read token from the keychain: {}", e)),
+        }
+    }
+
+    fn set(&self, secret: &str) -> Result<(), String> {
+        self.entry
+            .set_password(secret)
+            .map_err(|e| format!("Failed to save token to the keychain: {}", e))
+    }
+
+    fn delete(&self) -> Result<(), String> {
+        match self.entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to delete token from the keychain: {}", e)),
+        }
+    }
+}
+
+/// Secret encrypted with ChaCha20-Poly1305 under a random key kept in a second file
+/// Both files are private to the user. This keeps the token out of settings backups and
+/// casual reads, not away from someone who can read the user's files
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl EncryptedFileStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(CREDENTIALS_FILENAME),
+            key_path: dir.join(CREDENTIALS_KEY_FILENAME),
+        }
+    }
+
+    fn read_key(&self) -> Result<Option<Key>, String> {
+        match std::fs::read(&self.key_path) {
+            Ok(bytes) if bytes.len() == KEY_LEN => Ok(Some(*Key::from_slice(&bytes))),
+            Ok(_) => Err("Credential key file is corrupt".to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read credential key: {}", e)),
+        }
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn name(&self) -> &'static str {
+        "encrypted-file"
+    }
+
+    fn get(&self) -> Result<Option<String>, String> {
+        let data = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read stored token: {}", e)),
+        };
+        if data.len() <= NONCE_LEN {
+            return Err("Stored token is corrupt".to_string());
+        }
+        let key = self
+            .read_key()?
+            .ok_or("Credential key file is missing")?;
+
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = ChaCha20Poly1305::new(&key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt stored token".to_string())?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|_| "Stored token is corrupt".to_string())
+    }
+
+    fn set(&self, secret: &str) -> Result<(), String> {
+        let key = match self.read_key()? {
+            Some(key) => key,
+            None => {
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+                write_private(&self.key_path, key.as_slice())?;
+                key
+            }
+        };
+
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&key)
+            .encrypt(&nonce, secret.as_bytes())
+            .map_err(|_| "Failed to encrypt token".to_string())?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+        write_private(&self.path, &data)
+    }
+
+    fn delete(&self) -> Result<(), String> {
+        for path in [&self.path, &self.key_path] {
+            match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Failed to delete {}: {}", path.display(), e)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replace `path` with `contents`, readable and writable by the current user only
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(&tmp_path)
+        .map_err(|e| format!("Failed to create {}: {}", tmp_path.display(), e))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    drop(file);
+
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Keeps the GitHub token in the OS keychain, or in an encrypted file where there is none
+pub struct CredentialManager {
+    store: Box<dyn CredentialStore>,
+}
+
+impl CredentialManager {
+    pub fn new(app_dir: &Path) -> Self {
+        let store: Box<dyn CredentialStore> = match KeyringStore::open() {
+            Some(store) => Box::new(store),
+            None => Box::new(EncryptedFileStore::new(app_dir)),
+        };
+        log::info!("[Credentials] Storing the GitHub token in the {}", store.name());
+        Self::with_store(store)
+    }
+
+    pub fn with_store(store: Box<dyn CredentialStore>) -> Self {
+        Self { store }
+    }
+
+    /// Name of the backing store
+    pub fn backend(&self) -> &'static str {
+        self.store.name()
+    }
+
+    /// Stored token; read errors are logged and treated as no token
+    pub fn github_token(&self) -> Option<String> {
+        self.store.get().unwrap_or_else(|e| {
+            log::error!("[Credentials] {}", e);
+            None
+        })
+    }
+
+    pub fn set_github_token(&self, token: &str) -> Result<(), String> {
+        self.store.set(token)
+    }
+
+    pub fn delete_github_token(&self) -> Result<(), String> {
+        self.store.delete()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn encrypted_file_round_trip() {
+        let dir = TempDir::new("round-trip");
+        let store = EncryptedFileStore::new(&dir.0);
+
+        assert_eq!(store.get().unwrap(), None);
+        store.set("github_pat_first").unwrap();
+        store.set("github_pat_second").unwrap();
+        assert_eq!(store.get().unwrap().as_deref(), Some("github_pat_second"));
+
+        let on_disk = std::fs::read(dir.0.join(CREDENTIALS_FILENAME)).unwrap();
+        assert!(!on_disk.windows(6).any(|w| w == b"github"));
+
+        store.delete().unwrap();
+        assert_eq!(store.get().unwrap(), None);
+        assert!(!dir.0.join(CREDENTIALS_KEY_FILENAME).exists());
+        store.delete().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn encrypted_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("private");
+        EncryptedFileStore::new(&dir.0).set("ghp_secret").unwrap();
+
+        for name in [CREDENTIALS_FILENAME, CREDENTIALS_KEY_FILENAME] {
+            let mode = std::fs::metadata(dir.0.join(name)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_tampered_token() {
+        let dir = TempDir::new("tampered");
+        let store = EncryptedFileStore::new(&dir.0);
+        store.set("ghp_secret").unwrap();
+
+        let path = dir.0.join(CREDENTIALS_FILENAME);
+        let mut data = std::fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        std::fs::write(&path, data).unwrap();
+
+        assert!(store.get().is_err());
+        let manager = CredentialManager::with_store(Box::new(store));
+        assert_eq!(manager.github_token(), None);
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::billing_entity::BillingEntity;
+use crate::usage_query::UsageQuery;
+
+/// Version of the usage extractor script
+/// Bump it whenever the script changes what it fetches or the shape of what it reports
+pub const EXTRACTOR_VERSION: u32 = 3;
+
+const EXTRACTOR_SCRIPT: &str = include_str!("../assets/scripts/usage_extractor.js");
+
+/// Upper bound on usage table pages fetched per period
+const MAX_USAGE_TABLE_PAGES: u32 = 20;
+
+const USER_ENDPOINT: &str = "/api/v3/user";
+const ORGANIZATIONS_ENDPOINT: &str = "/api/v3/user/orgs";
+const ENTERPRISES_PAGE: &str = "/settings/enterprises";
+const USAGE_CARD_ENDPOINT: &str = "/settings/billing/copilot_usage_card";
+const USAGE_TABLE_ENDPOINT: &str = "/settings/billing/copilot_usage_table";
+
+/// Where the extractor runs, which decides how it reports results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtractorMode {
+    /// Login window: hands the result over by redirecting to the success URL
+    Auth,
+    /// Hidden webview: reports through the `hidden_webview_event` command
+    Hidden,
+}
+
+/// Extractor script for `entity` and `query`
+pub fn extractor_script(
+    mode: ExtractorMode,
+    entity: &BillingEntity,
+    query: &UsageQuery,
+) -> String {
+    render_extractor(mode, entity, query, Utc::now())
+}
+
+/// Extractor script preceded by its `EXTRACTOR` parameters
+pub fn render_extractor(
+    mode: ExtractorMode,
+    entity: &BillingEntity,
+    query: &UsageQuery,
+    now: DateTime<Utc>,
+) -> String {
+    let prefix = entity.path_prefix();
+    let params = serde_json::json!({
+        "version": EXTRACTOR_VERSION,
+        "mode": mode,
+        "entity": entity.kind,
+        "periods": query.period_params(now),
+        "group": query.group_param(),
+        "maxPages": MAX_USAGE_TABLE_PAGES,
+        "endpoints": {
+            "user": USER_ENDPOINT,
+            "organizations": ORGANIZATIONS_ENDPOINT,
+            "enterprises": ENTERPRISES_PAGE,
+            "usageCard": format!("{}{}", prefix, USAGE_CARD_ENDPOINT),
+            "usageTable": format!("{}{}", prefix, USAGE_TABLE_ENDPOINT),
+        },
+    });
+    format!("const EXTRACTOR = {};\n{}", params, EXTRACTOR_SCRIPT)
+}
+
+/// Extractor version echoed in a result payload
+/// Logged so extracted data can be traced back to the script that produced it
+pub fn reported_version(payload: &serde_json::Value) -> Option<u32> {
+    let version = payload
+        .get("extractorVersion")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    match version {
+        Some(v) if v != EXTRACTOR_VERSION => log::warn!(
+            "[Extractor] Result from extractor v{}, this build injects v{}",
+            v,
+            EXTRACTOR_VERSION
+        ),
+        Some(v) => log::debug!("[Extractor] Result from extractor v{}", v),
+        None => log::warn!("[Extractor] Result without an extractor version"),
+    }
+    version
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage_query::{UsageGrouping, UsagePeriod};
+    use chrono::TimeZone;
+
+    fn params_of(script: &str) -> serde_json::Value {
+        let line = script.lines().next().unwrap();
+        let json = line
+            .strip_prefix("const EXTRACTOR = ")
+            .and_then(|l| l.strip_suffix(';'))
+            .unwrap();
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn renders_parameters_before_the_script() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 3, 12, 0, 0).unwrap();
+        let query = UsageQuery::new(UsagePeriod::LastDays { days: 7 }, UsageGrouping::Model);
+
+        let personal = BillingEntity::personal();
+        let script = render_extractor(ExtractorMode::Hidden, &personal, &query, now);
+        let params = params_of(&script);
+
+        assert_eq!(params["version"], EXTRACTOR_VERSION);
+        assert_eq!(params["mode"], "hidden");
+        assert_eq!(params["entity"], "personal");
+        assert_eq!(params["periods"], serde_json::json!([4, 3]));
+        assert_eq!(params["group"], 1);
+        assert_eq!(params["endpoints"]["usageTable"], USAGE_TABLE_ENDPOINT);
+        assert!(script.ends_with(EXTRACTOR_SCRIPT));
+
+        let script =
+            render_extractor(ExtractorMode::Auth, &personal, &UsageQuery::default(), now);
+        let params = params_of(&script);
+        assert_eq!(params["mode"], "auth");
+        assert_eq!(params["periods"], serde_json::json!([3]));
+        assert_eq!(params["group"], 0);
+    }
+
+    #[test]
+    fn points_endpoints_at_the_entity() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 3, 12, 0, 0).unwrap();
+        let org = BillingEntity::organization("acme", None);
+
+        let params = params_of(&render_extractor(
+            ExtractorMode::Hidden,
+            &org,
+            &UsageQuery::default(),
+            now,
+        ));
+        assert_eq!(params["entity"], "organization");
+        assert_eq!(
+            params["endpoints"]["usageCard"],
+            "/organizations/acme/settings/billing/copilot_usage_card"
+        );
+        assert_eq!(params["endpoints"]["user"], USER_ENDPOINT);
+    }
+
+    #[test]
+    fn reads_reported_version() {
+        let payload = serde_json::json!({ "success": true, "extractorVersion": EXTRACTOR_VERSION });
+        assert_eq!(reported_version(&payload), Some(EXTRACTOR_VERSION));
+        assert_eq!(reported_version(&serde_json::json!({ "success": true })), None);
+    }
+}
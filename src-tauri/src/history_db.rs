@@ -0,0 +1,743 @@
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::notifications::NotificationState;
+use crate::usage::{CycleUsage, UsageEntry, UsageModel, UsageSnapshot};
+
+/// Schema migrations, applied in order; `PRAGMA user_version` records how many have run
+/// Never edit a shipped migration, append a new one instead
+const MIGRATIONS: &[&str] = &[
+    // v1: initial schema
+    "CREATE TABLE usage_snapshots (
+        timestamp INTEGER PRIMARY KEY,
+        used INTEGER NOT NULL,
+        usage_limit INTEGER NOT NULL,
+        billed_amount REAL NOT NULL
+    );
+    CREATE TABLE daily_usage (
+        timestamp INTEGER PRIMARY KEY,
+        used INTEGER NOT NULL,
+        usage_limit INTEGER NOT NULL,
+        included_requests INTEGER NOT NULL,
+        billed_requests INTEGER NOT NULL,
+        gross_amount REAL NOT NULL,
+        billed_amount REAL NOT NULL
+    );
+    CREATE TABLE model_usage (
+        day_timestamp INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        included_requests INTEGER NOT NULL,
+        billed_requests INTEGER NOT NULL,
+        gross_amount REAL NOT NULL,
+        billed_amount REAL NOT NULL,
+        PRIMARY KEY (day_timestamp, name)
+    );
+    CREATE TABLE fired_notifications (
+        cycle_key TEXT NOT NULL,
+        threshold INTEGER NOT NULL,
+        fired_at INTEGER NOT NULL,
+        PRIMARY KEY (cycle_key, threshold)
+    );",
+    // v2: currency of daily amounts
+    "ALTER TABLE daily_usage ADD COLUMN currency TEXT;",
+    // v3: totals of past billing cycles
+    "CREATE TABLE cycle_usage (
+        cycle_key TEXT PRIMARY KEY,
+        start_date TEXT NOT NULL,
+        end_date TEXT NOT NULL,
+        used INTEGER NOT NULL,
+        usage_limit INTEGER NOT NULL,
+        billed_amount REAL NOT NULL,
+        currency TEXT,
+        fetched_at INTEGER NOT NULL
+    );",
+];
+
+/// Why the history database could not be opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenError {
+    /// The file is not a readable SQLite database
+    Corrupt(String),
+    /// The schema was written by a newer version of the app
+    NewerSchema(String),
+    Other(String),
+}
+
+impl std::fmt::Display for OpenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Corrupt(message) | Self::NewerSchema(message) | Self::Other(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl OpenError {
+    /// Classify a SQLite error hit while opening, so only real corruption is recovered from
+    fn sqlite(context: &str, e: rusqlite::Error) -> Self {
+        let message = format!("{}: {}", context, e);
+        match e.sqlite_error_code() {
+            Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase) => Self::Corrupt(message),
+            _ => Self::Other(message),
+        }
+    }
+}
+
+/// Embedded SQLite store for usage history, snapshots, cycle totals and fired notifications
+pub struct HistoryDb {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryDb {
+    /// Open (or create) the database at `path` and bring its schema up to date
+    pub fn open(path: &Path) -> Result<Self, OpenError> {
+        let mut conn = Connection::open(path)
+            .map_err(|e| OpenError::sqlite("Failed to open history database", e))?;
+
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| OpenError::sqlite("Failed to enable WAL mode", e))?;
+        Self::migrate(&mut conn)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Rename a corrupt database at `path`, with its WAL and shared-memory files, to
+    /// `<name>.corrupt-<timestamp>` so repeated corruption never overwrites an earlier copy
+    pub fn move_aside(path: &Path) -> Result<(), String> {
+        let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+        for suffix in ["", "-wal", "-shm"] {
+            let mut from = path.as_os_str().to_owned();
+            from.push(suffix);
+            let from = std::path::PathBuf::from(from);
+            if !from.exists() {
+                continue;
+            }
+            let mut to = path.as_os_str().to_owned();
+            to.push(format!(".corrupt-{}{}", stamp, suffix));
+            std::fs::rename(&from, &to)
+                .map_err(|e| format!("Failed to move aside history database: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Current schema version of the database
+    pub fn schema_version(&self) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+        Self::user_version(&conn).map_err(|e| format!("Failed to read schema version: {}", e))
+    }
+
+    fn user_version(conn: &Connection) -> rusqlite::Result<usize> {
+        conn.pragma_query_value(None, "user_version", |row| row.get::<_, i64>(0))
+            .map(|v| v as usize)
+    }
+
+    /// Apply pending migrations, each in its own transaction
+    fn migrate(conn: &mut Connection) -> Result<(), OpenError> {
+        let current = Self::user_version(conn)
+            .map_err(|e| OpenError::sqlite("Failed to read schema version", e))?;
+        if current > MIGRATIONS.len() {
+            return Err(OpenError::NewerSchema(format!(
+                "History database schema v{} is newer than this app supports (v{})",
+                current,
+                MIGRATIONS.len()
+            )));
+        }
+
+        for (index, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+            let version = index + 1;
+            let tx = conn
+                .transaction()
+                .map_err(|e| OpenError::sqlite("Failed to start migration", e))?;
+            tx.execute_batch(sql).map_err(|e| {
+                OpenError::sqlite(&format!("Failed to apply migration v{}", version), e)
+            })?;
+            tx.pragma_update(None, "user_version", version as i64)
+                .map_err(|e| OpenError::sqlite("Failed to record schema version", e))?;
+            tx.commit().map_err(|e| {
+                OpenError::sqlite(&format!("Failed to commit migration v{}", version), e)
+            })?;
+            log::info!("[HistoryDb] Applied migration v{}", version);
+        }
+
+        Ok(())
+    }
+
+    /// Insert or replace daily rows (and their per-model rows), keyed by day timestamp
+    pub fn upsert_daily_entries(&self, entries: &[UsageEntry]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        for entry in entries {
+            tx.execute(
+                "INSERT OR REPLACE INTO daily_usage
+                    (timestamp, used, usage_limit, included_requests, billed_requests, gross_amount, billed_amount, currency)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    entry.timestamp,
+                    entry.used,
+                    entry.limit,
+                    entry.included_requests,
+                    entry.billed_requests,
+                    entry.gross_amount,
+                    entry.billed_amount,
+                    entry.currency
+                ],
+            )
+            .map_err(|e| format!("Failed to save daily usage: {}", e))?;
+
+            tx.execute(
+                "DELETE FROM model_usage WHERE day_timestamp = ?1",
+                params![entry.timestamp],
+            )
+            .map_err(|e| format!("Failed to clear model usage: {}", e))?;
+            for model in &entry.models {
+                tx.execute(
+                    "INSERT OR REPLACE INTO model_usage
+                        (day_timestamp, name, included_requests, billed_requests, gross_amount, billed_amount)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        entry.timestamp,
+                        model.name,
+                        model.included_requests,
+                        model.billed_requests,
+                        model.gross_amount,
+                        model.billed_amount
+                    ],
+                )
+                .map_err(|e| format!("Failed to save model usage: {}", e))?;
+            }
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit daily usage: {}", e))
+    }
+
+    /// All daily rows with their models, newest first
+    pub fn load_daily_entries(&self) -> Result<Vec<UsageEntry>, String> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut models: HashMap<i64, Vec<UsageModel>> = HashMap::new();
+        let mut stmt = conn
+            .prepare(
+                "SELECT day_timestamp, name, included_requests, billed_requests, gross_amount, billed_amount
+                 FROM model_usage ORDER BY day_timestamp, name",
+            )
+            .map_err(|e| format!("Failed to query model usage: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    UsageModel {
+                        name: row.get(1)?,
+                        included_requests: row.get(2)?,
+                        billed_requests: row.get(3)?,
+                        gross_amount: row.get(4)?,
+                        billed_amount: row.get(5)?,
+                    },
+                ))
+            })
+            .map_err(|e| format!("Failed to query model usage: {}", e))?;
+        for row in rows {
+            let (day, model) = row.map_err(|e| format!("Failed to read model usage: {}", e))?;
+            models.entry(day).or_default().push(model);
+        }
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT timestamp, used, usage_limit, included_requests, billed_requests, gross_amount, billed_amount, currency
+                 FROM daily_usage ORDER BY timestamp DESC",
+            )
+            .map_err(|e| format!("Failed to query daily usage: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(UsageEntry {
+                    timestamp: row.get(0)?,
+                    used: row.get(1)?,
+                    limit: row.get(2)?,
+                    included_requests: row.get(3)?,
+                    billed_requests: row.get(4)?,
+                    gross_amount: row.get(5)?,
+                    billed_amount: row.get(6)?,
+                    currency: row.get(7)?,
+                    models: Vec::new(),
+                })
+            })
+            .map_err(|e| format!("Failed to query daily usage: {}", e))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let mut entry = row.map_err(|e| format!("Failed to read daily usage: {}", e))?;
+            entry.models = models.remove(&entry.timestamp).unwrap_or_default();
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Insert snapshots; a second sample with the same timestamp replaces the first
+    pub fn insert_snapshots(&self, snapshots: &[UsageSnapshot]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        for snapshot in snapshots {
+            tx.execute(
+                "INSERT OR REPLACE INTO usage_snapshots (timestamp, used, usage_limit, billed_amount)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    snapshot.timestamp,
+                    snapshot.used,
+                    snapshot.limit,
+                    snapshot.billed_amount
+                ],
+            )
+            .map_err(|e| format!("Failed to save usage snapshot: {}", e))?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit usage snapshots: {}", e))
+    }
+
+    /// Snapshots recorded at or after `since` (all when `None`), oldest first
+    pub fn load_snapshots(&self, since: Option<i64>) -> Result<Vec<UsageSnapshot>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT timestamp, used, usage_limit, billed_amount FROM usage_snapshots
+                 WHERE timestamp >= ?1 ORDER BY timestamp",
+            )
+            .map_err(|e| format!("Failed to query usage snapshots: {}", e))?;
+        let rows = stmt
+            .query_map(params![since.unwrap_or(i64::MIN)], |row| {
+                Ok(UsageSnapshot {
+                    timestamp: row.get(0)?,
+                    used: row.get(1)?,
+                    limit: row.get(2)?,
+                    billed_amount: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query usage snapshots: {}", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read usage snapshots: {}", e))
+    }
+
+    /// Drop snapshots before `retention_cutoff` and keep only the last snapshot per
+    /// `bucket_secs` bucket for those before `compaction_cutoff`
+    /// Returns the number of snapshots removed
+    pub fn compact_snapshots(
+        &self,
+        retention_cutoff: Option<i64>,
+        compaction_cutoff: i64,
+        bucket_secs: i64,
+    ) -> Result<usize, String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        let mut removed = 0;
+        if let Some(cutoff) = retention_cutoff {
+            removed += tx
+                .execute(
+                    "DELETE FROM usage_snapshots WHERE timestamp < ?1",
+                    params![cutoff],
+                )
+                .map_err(|e| format!("Failed to apply snapshot retention: {}", e))?;
+        }
+        removed += tx
+            .execute(
+                "DELETE FROM usage_snapshots
+                 WHERE timestamp < ?1
+                   AND timestamp NOT IN (
+                       SELECT MAX(timestamp) FROM usage_snapshots
+                       WHERE timestamp < ?1
+                       GROUP BY timestamp / ?2
+                   )",
+                params![compaction_cutoff, bucket_secs.max(1)],
+            )
+            .map_err(|e| format!("Failed to compact usage snapshots: {}", e))?;
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit snapshot compaction: {}", e))?;
+
+        Ok(removed)
+    }
+
+    /// Insert or replace the totals of a billing cycle
+    pub fn upsert_cycle_usage(&self, usage: &CycleUsage) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO cycle_usage
+             (cycle_key, start_date, end_date, used, usage_limit, billed_amount, currency, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                usage.cycle_key,
+                usage.start_date,
+                usage.end_date,
+                usage.used,
+                usage.limit,
+                usage.billed_amount,
+                usage.currency,
+                usage.fetched_at
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save cycle usage: {}", e))
+    }
+
+    /// Stored totals of the billing cycle `cycle_key`, if any
+    pub fn load_cycle_usage(&self, cycle_key: &str) -> Result<Option<CycleUsage>, String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT cycle_key, start_date, end_date, used, usage_limit, billed_amount, currency, fetched_at
+             FROM cycle_usage WHERE cycle_key = ?1",
+            params![cycle_key],
+            |row| {
+                Ok(CycleUsage {
+                    cycle_key: row.get(0)?,
+                    start_date: row.get(1)?,
+                    end_date: row.get(2)?,
+                    used: row.get(3)?,
+                    limit: row.get(4)?,
+                    billed_amount: row.get(5)?,
+                    currency: row.get(6)?,
+                    fetched_at: row.get(7)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to query cycle usage: {}", e))
+    }
+
+    /// Fired thresholds of the most recently notified billing cycle
+    pub fn load_notification_state(&self) -> Result<NotificationState, String> {
+        let conn = self.conn.lock().unwrap();
+        let cycle_key: Option<String> = conn
+            .query_row(
+                "SELECT cycle_key FROM fired_notifications ORDER BY fired_at DESC, cycle_key DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query fired notifications: {}", e))?;
+        let cycle_key = match cycle_key {
+            Some(cycle_key) => cycle_key,
+            None => return Ok(NotificationState::default()),
+        };
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT threshold FROM fired_notifications WHERE cycle_key = ?1 ORDER BY threshold",
+            )
+            .map_err(|e| format!("Failed to query fired notifications: {}", e))?;
+        let fired_thresholds = stmt
+            .query_map(params![cycle_key], |row| row.get::<_, u32>(0))
+            .map_err(|e| format!("Failed to query fired notifications: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read fired notifications: {}", e))?;
+
+        Ok(NotificationState {
+            cycle_key,
+            fired_thresholds,
+        })
+    }
+
+    /// Make the fired thresholds for `state.cycle_key` match `state`
+    /// Rows of other cycles are kept as notification history
+    pub fn save_notification_state(&self, state: &NotificationState) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let now = chrono::Utc::now().timestamp();
+
+        let existing: Vec<u32> = {
+            let mut stmt = tx
+                .prepare("SELECT threshold FROM fired_notifications WHERE cycle_key = ?1")
+                .map_err(|e| format!("Failed to query fired notifications: {}", e))?;
+            let rows = stmt
+                .query_map(params![state.cycle_key], |row| row.get::<_, u32>(0))
+                .map_err(|e| format!("Failed to query fired notifications: {}", e))?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to read fired notifications: {}", e))?
+        };
+
+        for threshold in existing.iter().filter(|t| !state.fired_thresholds.contains(t)) {
+            tx.execute(
+                "DELETE FROM fired_notifications WHERE cycle_key = ?1 AND threshold = ?2",
+                params![state.cycle_key, threshold],
+            )
+            .map_err(|e| format!("Failed to delete fired notification: {}", e))?;
+        }
+        for threshold in &state.fired_thresholds {
+            tx.execute(
+                "INSERT OR IGNORE INTO fired_notifications (cycle_key, threshold, fired_at)
+                 VALUES (?1, ?2, ?3)",
+                params![state.cycle_key, threshold, now],
+            )
+            .map_err(|e| format!("Failed to save fired notification: {}", e))?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit fired notifications: {}", e))
+    }
+
+    /// Delete all stored history, snapshots, cycle totals and fired notifications
+    pub fn clear(&self) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch(
+            "BEGIN;
+             DELETE FROM usage_snapshots;
+             DELETE FROM daily_usage;
+             DELETE FROM model_usage;
+             DELETE FROM fired_notifications;
+             DELETE FROM cycle_usage;
+             COMMIT;",
+        )
+        .map_err(|e| format!("Failed to clear history database: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::StoreManager;
+    use crate::test_support::TempDir;
+    use std::path::PathBuf;
+
+    fn db_path(dir: &TempDir) -> PathBuf {
+        dir.0.join("history.db")
+    }
+
+    fn entry(timestamp: i64, used: u32) -> UsageEntry {
+        UsageEntry {
+            timestamp,
+            used,
+            limit: 300,
+            included_requests: used,
+            billed_requests: 0,
+            gross_amount: 0.0,
+            billed_amount: 0.0,
+            currency: Some("USD".to_string()),
+            models: vec![UsageModel {
+                name: "o3".to_string(),
+                included_requests: used,
+                billed_requests: 0,
+                gross_amount: 0.0,
+                billed_amount: 0.0,
+            }],
+        }
+    }
+
+    fn snapshot(timestamp: i64) -> UsageSnapshot {
+        UsageSnapshot {
+            timestamp,
+            used: 1,
+            limit: 300,
+            billed_amount: 0.0,
+        }
+    }
+
+    fn corrupt_copies(dir: &TempDir) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(&dir.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.contains(".corrupt-"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn timestamps(db: &HistoryDb) -> Vec<i64> {
+        db.load_snapshots(None).unwrap().iter().map(|s| s.timestamp).collect()
+    }
+
+    #[test]
+    fn creates_a_fresh_database() {
+        let dir = TempDir::new("fresh");
+        let db = HistoryDb::open(&db_path(&dir)).unwrap();
+
+        assert_eq!(db.schema_version().unwrap(), MIGRATIONS.len());
+        assert!(db.load_daily_entries().unwrap().is_empty());
+        assert!(db.load_snapshots(None).unwrap().is_empty());
+        assert!(db.load_cycle_usage("2026-02").unwrap().is_none());
+        assert!(db.load_notification_state().unwrap().cycle_key.is_empty());
+    }
+
+    #[test]
+    fn reopens_a_migrated_database() {
+        let dir = TempDir::new("reopen");
+        {
+            let db = HistoryDb::open(&db_path(&dir)).unwrap();
+            db.upsert_daily_entries(&[entry(86_400, 10), entry(0, 5)]).unwrap();
+            db.upsert_daily_entries(&[entry(86_400, 12)]).unwrap();
+        }
+
+        let db = HistoryDb::open(&db_path(&dir)).unwrap();
+        assert_eq!(db.schema_version().unwrap(), MIGRATIONS.len());
+        let entries = db.load_daily_entries().unwrap();
+        let used: Vec<u32> = entries.iter().map(|e| e.used).collect();
+        assert_eq!(used, [12, 5]);
+        assert_eq!(entries[0].currency.as_deref(), Some("USD"));
+        assert_eq!(entries[0].models.len(), 1);
+        assert_eq!(entries[0].models[0].included_requests, 12);
+    }
+
+    #[test]
+    fn migrates_an_older_schema() {
+        let dir = TempDir::new("migrate");
+        {
+            let conn = Connection::open(db_path(&dir)).unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.pragma_update(None, "user_version", 1).unwrap();
+            conn.execute(
+                "INSERT INTO daily_usage VALUES (0, 5, 300, 5, 0, 0.0, 0.0)",
+                [],
+            )
+            .unwrap();
+        }
+
+        let db = HistoryDb::open(&db_path(&dir)).unwrap();
+        assert_eq!(db.schema_version().unwrap(), MIGRATIONS.len());
+        let entries = db.load_daily_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].currency, None);
+    }
+
+    #[test]
+    fn refuses_a_newer_schema() {
+        let dir = TempDir::new("newer");
+        {
+            let conn = Connection::open(db_path(&dir)).unwrap();
+            conn.pragma_update(None, "user_version", MIGRATIONS.len() as i64 + 1).unwrap();
+        }
+
+        let error = HistoryDb::open(&db_path(&dir)).err().unwrap();
+        assert!(matches!(error, OpenError::NewerSchema(_)), "{}", error);
+
+        // The store keeps the database in place and refuses to start
+        assert!(StoreManager::new(dir.0.clone()).is_err());
+        assert!(db_path(&dir).exists());
+        assert_eq!(corrupt_copies(&dir), Vec::<String>::new());
+    }
+
+    #[test]
+    fn moves_aside_a_corrupt_database() {
+        let dir = TempDir::new("corrupt");
+        std::fs::write(db_path(&dir), vec![0x42; 4096]).unwrap();
+
+        let error = HistoryDb::open(&db_path(&dir)).err().unwrap();
+        assert!(matches!(error, OpenError::Corrupt(_)), "{}", error);
+
+        let store = StoreManager::new(dir.0.clone()).unwrap();
+        assert!(store.get_usage_history().is_empty());
+        let copies = corrupt_copies(&dir);
+        assert_eq!(copies.len(), 1, "{:?}", copies);
+        assert!(copies[0].starts_with("history.db.corrupt-"), "{:?}", copies);
+    }
+
+    #[test]
+    fn moves_aside_the_journal_files_too() {
+        let dir = TempDir::new("move-aside");
+        for name in ["history.db", "history.db-wal", "history.db-shm"] {
+            std::fs::write(dir.0.join(name), name).unwrap();
+        }
+
+        HistoryDb::move_aside(&db_path(&dir)).unwrap();
+        let copies = corrupt_copies(&dir);
+        assert_eq!(copies.len(), 3, "{:?}", copies);
+        for (copy, suffix) in copies.iter().zip(["", "-shm", "-wal"]) {
+            let original = format!("history.db{}", suffix);
+            assert_eq!(std::fs::read_to_string(dir.0.join(copy)).unwrap(), original);
+            assert!(!dir.0.join(original).exists());
+        }
+    }
+
+    #[test]
+    fn compacts_old_snapshots_per_bucket() {
+        let dir = TempDir::new("compact");
+        let db = HistoryDb::open(&db_path(&dir)).unwrap();
+        let all: Vec<UsageSnapshot> = [0, 100, 3500, 3700, 8000, 8100].into_iter().map(snapshot).collect();
+        db.insert_snapshots(&all).unwrap();
+
+        // 0 is past retention; of 100 and 3500 only the bucket's last is kept; newer ones stay
+        assert_eq!(db.compact_snapshots(Some(50), 7200, 3600).unwrap(), 2);
+        assert_eq!(timestamps(&db), [3500, 3700, 8000, 8100]);
+        assert_eq!(db.compact_snapshots(Some(50), 7200, 3600).unwrap(), 0);
+        assert_eq!(db.load_snapshots(Some(3700)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn round_trips_fired_notifications() {
+        let dir = TempDir::new("notifications");
+        let db = HistoryDb::open(&db_path(&dir)).unwrap();
+        let state = |cycle_key: &str, fired_thresholds: Vec<u32>| NotificationState {
+            cycle_key: cycle_key.to_string(),
+            fired_thresholds,
+        };
+
+        db.save_notification_state(&state("2026-02", vec![50, 75])).unwrap();
+        let loaded = db.load_notification_state().unwrap();
+        assert_eq!(loaded.cycle_key, "2026-02");
+        assert_eq!(loaded.fired_thresholds, [50, 75]);
+
+        db.save_notification_state(&state("2026-02", vec![50])).unwrap();
+        assert_eq!(db.load_notification_state().unwrap().fired_thresholds, [50]);
+
+        db.save_notification_state(&state("2026-03", vec![90])).unwrap();
+        let loaded = db.load_notification_state().unwrap();
+        assert_eq!(loaded.cycle_key, "2026-03");
+        assert_eq!(loaded.fired_thresholds, [90]);
+
+        db.clear().unwrap();
+        assert!(db.load_notification_state().unwrap().cycle_key.is_empty());
+    }
+
+    #[test]
+    fn imports_legacy_files_once() {
+        let dir = TempDir::new("legacy");
+        let now = chrono::Utc::now().timestamp();
+        let write_legacy_files = || {
+            std::fs::write(
+                dir.0.join("usage_history.json"),
+                serde_json::to_string(&[entry(86_400, 10), entry(0, 5)]).unwrap(),
+            )
+            .unwrap();
+            let lines: Vec<String> = [now - 60, now - 30]
+                .into_iter()
+                .map(|t| serde_json::to_string(&snapshot(t)).unwrap())
+                .collect();
+            std::fs::write(dir.0.join("usage_snapshots.jsonl"), lines.join("\n") + "\n{\"trunc").unwrap();
+            std::fs::write(
+                dir.0.join("notification_state.json"),
+                r#"{ "cycleKey": "2026-02", "firedThresholds": [50, 75] }"#,
+            )
+            .unwrap();
+        };
+
+        write_legacy_files();
+        let store = StoreManager::new(dir.0.clone()).unwrap();
+        assert_eq!(store.get_usage_history().len(), 2);
+        assert_eq!(store.get_usage_snapshots(None).len(), 2);
+        assert_eq!(store.get_notification_state().fired_thresholds, [50, 75]);
+        assert!(!dir.0.join("usage_history.json").exists());
+        assert!(dir.0.join("usage_history.json.imported").exists());
+        drop(store);
+
+        // Importing the same files again must not duplicate anything
+        write_legacy_files();
+        let store = StoreManager::new(dir.0.clone()).unwrap();
+        assert_eq!(store.get_usage_history().len(), 2);
+        assert_eq!(store.get_usage_snapshots(None).len(), 2);
+        assert_eq!(store.get_notification_state().fired_thresholds, [50, 75]);
+    }
+}
@@ -1,10 +1,59 @@
+mod accounts;
 mod auth;
+mod backoff;
+mod billing;
+mod billing_entity;
+mod calendar;
+mod credentials;
+mod extractor;
+mod history_db;
+mod money;
+mod notifications;
+mod persist;
+mod rest_source;
+mod settings_migration;
 mod store;
+#[cfg(test)]
+mod test_support;
 mod tray_icon_renderer;
+mod update;
 mod usage;
+mod usage_query;
+mod usage_source;
 
-pub use auth::{AuthManager, AuthState, ExtractionResult, UsageData, hidden_webview_event, HiddenWebviewEvent};
+pub use accounts::{
+    account_name, account_usages, aggregate_usage, expired_account, supports_isolated_webviews,
+    with_account_store, AccountProfile, AccountStores,
+    AccountUsage, WebviewProfile, DEFAULT_ACCOUNT_ID, ISOLATION_UNSUPPORTED,
+};
+pub use auth::{AuthManager, AuthState, ExtractionResult, SchemaDriftError, UsageData, hidden_webview_event, HiddenWebviewEvent};
+pub use backoff::{CircuitState, PollBackoff, PollingHealth, PollingStatus};
+pub use billing::BillingCycle;
+pub use billing_entity::{BillingEntity, BillingEntityKind, PERSONAL_ENTITY_KEY};
+pub use calendar::{Clock, FixedClock, SystemClock};
+pub use credentials::{CredentialManager, CredentialStore, EncryptedFileStore, KeyringStore};
+pub use extractor::{ExtractorMode, EXTRACTOR_VERSION};
+pub use history_db::HistoryDb;
+pub use money::{parse_count, parse_money, Money};
+pub use notifications::NotificationState;
+pub use settings_migration::CURRENT_SETTINGS_VERSION;
 // REMOVED init_store_manager - StoreManager is now initialized in main() before builder
-pub use store::{AppSettings, StoreManager, UsageCache, WidgetPosition};
+pub use rest_source::{revoke_token, validate_token, RestUsageSource, TokenInfo};
+pub use store::{
+    AppSettings, StoreManager, UsageCache, WidgetPosition, UPDATE_CHANNELS, USAGE_SOURCES,
+};
 pub use tray_icon_renderer::{TrayIconRenderer, TrayImage};
-pub use usage::{UsageEntry, UsageHistory, UsageManager, UsagePayload, UsageSummary};
+pub use update::{
+    download_verified_asset, parse_expected_checksum, parse_release_version, rank_release_assets,
+    select_release_for_channel, ReleaseAsset, UpdateTarget, GITHUB_RELEASES_API_URL,
+};
+pub use usage::{
+    format_age, is_stale, stale_after_secs, CycleComparison, CycleUsage, PeriodUsage,
+    RefreshOutcome, UsageEntry, UsageHistory, UsageManager, UsagePayload, UsageSnapshot,
+    UsageSummary,
+};
+pub use usage_query::{UsageGrouping, UsagePeriod, UsageQuery, MAX_LAST_DAYS};
+pub use usage_source::{
+    configured_entity_source, configured_source, FixtureUsageSource, UsageSource,
+    WebviewUsageSource,
+};
@@ -4,7 +4,6 @@
     windows_subsystem = "windows"
 )]
 
-use chrono::Datelike;
 use serde::Deserialize;
 use std::sync::{Arc, Mutex};
 use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
@@ -15,12 +14,11 @@ use tauri_plugin_notification::NotificationExt;
 use tauri_plugin_opener::OpenerExt;
 
 use copilot_tracker::{
-    AuthManager, StoreManager, TrayIconRenderer, UsageManager, WidgetPosition,
+    AccountStores, AuthManager, BillingCycle, Clock, CredentialManager, StoreManager, SystemClock, TrayIconRenderer, UsageManager, UsageSource,
+    WidgetPosition, GITHUB_RELEASES_API_URL,
 };
 mod theme;
 
-const GITHUB_API_URL: &str = "https://api.github.com/repos/bizzkoot/copilot-tracker/releases/latest";
-
 use crate::theme::text_color_for_theme_preference;
 
 // ============================================================================
@@ -63,6 +61,9 @@ struct TrayState {
 // Background Polling State
 // ============================================================================
 
+/// How often the tray rechecks whether the usage it shows has gone stale (seconds)
+const STALENESS_CHECK_SECS: u64 = 5 * 60;
+
 /// Debounce window for polling restart (milliseconds)
 const POLLING_RESTART_DEBOUNCE_MS: u64 = 500;
 
@@ -174,12 +175,26 @@ struct UpdateCheckStatus {
 struct UpdateInfo {
     version: String,
     release_url: String,
+    /// Download URL of the best installer for this platform
     download_url: Option<String>,
+    /// Best installer for this platform
+    download_asset: Option<copilot_tracker::ReleaseAsset>,
+    /// All installers compatible with this platform, best match first
+    assets: Vec<copilot_tracker::ReleaseAsset>,
     release_name: Option<String>,
     release_notes: Option<String>,
     release_date: Option<String>,
 }
 
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateDownloadProgress {
+    asset_name: String,
+    downloaded: u64,
+    total: Option<u64>,
+    percent: Option<f32>,
+}
+
 #[derive(Default)]
 struct UpdateState {
     latest: Mutex<Option<UpdateInfo>>,
@@ -257,7 +272,7 @@ fn update_tray_icon(
 
     let image = state
         .renderer
-        .render_text_only(&text, 16, color)
+        .render_text(&text, 16, color, tray_is_stale(app))
         .into_tauri_image();
 
     let tray_guard = state.tray.lock().map_err(|_| "tray lock poisoned".to_string())?;
@@ -274,15 +289,83 @@ fn update_tray_icon(
     Ok(())
 }
 
+/// Usage shown in the tray: the displayed account's, or the sum over all signed-in
+/// accounts when `aggregateTrayText` is on
+fn displayed_usage(app: &AppHandle) -> (u32, u32) {
+    let settings = app.state::<StoreManager>().get_settings();
+    if settings.aggregate_tray_text {
+        return copilot_tracker::aggregate_usage(&copilot_tracker::account_usages(app));
+    }
+    copilot_tracker::with_account_store(app, settings.displayed_account_id(), |store| store.get_usage())
+        .unwrap_or_else(|e| {
+            log::error!("[Accounts] {}", e);
+            app.state::<StoreManager>().get_usage()
+        })
+}
+
+/// When the usage shown in the tray was fetched: the displayed account's, or the oldest
+/// signed-in account's when the tray shows the total; 0 when there is none
+fn displayed_fetch_timestamp(app: &AppHandle) -> i64 {
+    let settings = app.state::<StoreManager>().get_settings();
+    let ids: Vec<String> = if settings.aggregate_tray_text {
+        settings.accounts.into_iter().map(|a| a.id).collect()
+    } else {
+        vec![settings.displayed_account_id().to_string()]
+    };
+    ids.iter()
+        .filter_map(|id| {
+            copilot_tracker::with_account_store(app, id, |store| {
+                store.is_authenticated().then(|| store.get_last_fetch_timestamp())
+            })
+            .ok()
+            .flatten()
+        })
+        .filter(|timestamp| *timestamp > 0)
+        .min()
+        .unwrap_or(0)
+}
+
+/// Whether the usage shown in the tray is older than the staleness threshold
+fn tray_is_stale(app: &AppHandle) -> bool {
+    let refresh_interval = app.state::<StoreManager>().get_settings().refresh_interval;
+    copilot_tracker::is_stale(
+        displayed_fetch_timestamp(app),
+        refresh_interval,
+        chrono::Utc::now().timestamp(),
+    )
+}
+
 /// Helper to update tray icon using current settings from store
 fn update_tray_icon_from_store(app: &AppHandle) -> Result<(), String> {
     let store = app.state::<StoreManager>();
-    let (used, limit) = store.get_usage();
+    let (used, limit) = displayed_usage(app);
     let format = store.get_tray_icon_format();
     let tray_state = app.state::<TrayState>();
     update_tray_icon(app, &tray_state, used, limit, &format)
 }
 
+/// Tray line for a failing background poll; `None` while polls succeed
+fn polling_status_label(status: &copilot_tracker::PollingStatus) -> Option<String> {
+    let next_poll = status
+        .next_poll_at
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.with_timezone(&chrono::Local));
+    match status.state {
+        copilot_tracker::CircuitState::Closed => None,
+        copilot_tracker::CircuitState::BackingOff => Some(format!(
+            "⚠️ {} failed refresh{}, retrying at {}",
+            status.consecutive_failures,
+            if status.consecutive_failures == 1 { "" } else { "es" },
+            format_timestamp(next_poll)
+        )),
+        copilot_tracker::CircuitState::Open => Some(format!(
+            "⏸ Refresh paused after {} failures, next try at {}",
+            status.consecutive_failures,
+            format_timestamp(next_poll)
+        )),
+    }
+}
+
 fn build_tray_menu(
     app: &AppHandle,
     update: Option<&UpdateInfo>,
@@ -290,33 +373,86 @@ fn build_tray_menu(
     let store = app.state::<StoreManager>();
     let settings = store.get_settings();
     let version = app.package_info().version.to_string();
-    let (used, limit) = store.get_usage();
-    let usage_history = UsageManager::get_cached_history(app);
-    let prediction = UsageManager::predict_usage_from_history(&usage_history, used, limit, settings.prediction_period);
+    // Usage details follow the account shown in the tray
+    let ((used, limit), usage_history, last_fetch_timestamp, signed_in) = copilot_tracker::with_account_store(
+        app,
+        settings.displayed_account_id(),
+        |account| {
+            (
+                account.get_usage(),
+                UsageManager::cached_history(account),
+                account.get_last_fetch_timestamp(),
+                account.is_authenticated(),
+            )
+        },
+    )?;
+    let now = SystemClock.now();
+    let cycle = BillingCycle::current(&settings, &SystemClock);
+    let prediction = UsageManager::predict_usage_from_history(&usage_history, used, limit, &settings, &SystemClock);
     
     // Calculate metrics for dual-perspective display
     let remaining = limit.saturating_sub(used);
     let percentage_used = if limit > 0 { (used as f32 / limit as f32) * 100.0 } else { 0.0 };
     let percentage_remaining = 100.0 - percentage_used;
     
-    // Calculate daily metrics
-    let now = chrono::Utc::now();
-    let current_day = now.day() as f32;
-    let days_in_month = if now.month() == 12 {
-        31
-    } else {
-        let next_month = chrono::NaiveDate::from_ymd_opt(now.year(), now.month() + 1, 1)
-            .unwrap_or_else(|| chrono::NaiveDate::from_ymd_opt(now.year() + 1, 1, 1).unwrap());
-        let current_month =
-            chrono::NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap();
-        (next_month - current_month).num_days() as u32
-    };
-    let days_remaining = days_in_month as f32 - current_day;
+    // Calculate daily metrics for the current billing cycle
+    let current_day = cycle.day_of_cycle(now) as f32;
+    let days_remaining = cycle.remaining_days(now) as f32;
     let daily_rate = if current_day > 0.0 { used as f32 / current_day } else { 0.0 };
     // Floor the daily budget to be conservative (synced with Dashboard)
     let daily_budget = if days_remaining > 0.0 { (remaining as f32 / days_remaining).floor() } else { 0.0 };
 
     let menu = Menu::new(app).map_err(|e| e.to_string())?;
+
+    // === ACCOUNTS SECTION ===
+    if settings.accounts.len() > 1 {
+        let displayed_name = settings
+            .accounts
+            .iter()
+            .find(|a| a.id == settings.displayed_account_id())
+            .map_or("Default", |a| a.name.as_str());
+        let accounts_submenu = Submenu::with_id(
+            app,
+            "accounts",
+            format!("👤 Account: {} ▶", displayed_name),
+            true,
+        )
+        .map_err(|e| e.to_string())?;
+        for account in copilot_tracker::account_usages(app) {
+            let label = if account.is_authenticated {
+                format!("{} ({}/{})", account.name, account.used, account.limit)
+            } else {
+                format!("{} (signed out)", account.name)
+            };
+            let item = CheckMenuItem::with_id(
+                app,
+                format!("account:{}", account.id),
+                label,
+                true,
+                account.id == settings.displayed_account_id(),
+                None::<&str>,
+            )
+            .map_err(|e| e.to_string())?;
+            accounts_submenu.append(&item).map_err(|e| e.to_string())?;
+        }
+        accounts_submenu
+            .append(&PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        let aggregate_item = CheckMenuItem::with_id(
+            app,
+            "aggregate_tray_text",
+            "Show Total of All Accounts",
+            true,
+            settings.aggregate_tray_text,
+            None::<&str>,
+        )
+        .map_err(|e| e.to_string())?;
+        accounts_submenu.append(&aggregate_item).map_err(|e| e.to_string())?;
+        menu.append(&accounts_submenu).map_err(|e| e.to_string())?;
+
+        menu.append(&PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+    }
     
     // === USAGE OVERVIEW SECTION ===
     // === USAGE OVERVIEW SECTION ===
@@ -483,8 +619,6 @@ fn build_tray_menu(
     menu.append(&refresh).map_err(|e| e.to_string())?;
     
     // Show last refresh time below Refresh (from persisted store)
-    let store = app.state::<StoreManager>();
-    let last_fetch_timestamp = store.get_last_fetch_timestamp();
     let last_refresh_time = if last_fetch_timestamp > 0 {
         chrono::DateTime::from_timestamp(last_fetch_timestamp, 0)
             .map(|dt| dt.with_timezone(&chrono::Local))
@@ -496,6 +630,45 @@ fn build_tray_menu(
         .map_err(|e| e.to_string())?;
     menu.append(&last_refresh_item).map_err(|e| e.to_string())?;
 
+    let now = chrono::Utc::now().timestamp();
+    if signed_in && copilot_tracker::is_stale(last_fetch_timestamp, settings.refresh_interval, now) {
+        let stale_label = format!(
+            "⚠️ Data is {} old",
+            copilot_tracker::format_age(now - last_fetch_timestamp)
+        );
+        let stale_item = MenuItem::with_id(app, "stale_data", stale_label, false, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        menu.append(&stale_item).map_err(|e| e.to_string())?;
+    }
+
+    // Cached numbers are all there is until the expired session is renewed
+    if let Some(account) = copilot_tracker::expired_account(app) {
+        let label = if account.id == copilot_tracker::DEFAULT_ACCOUNT_ID {
+            "🔒 Session Expired – Sign In Again".to_string()
+        } else {
+            format!("🔒 {} Session Expired – Sign In Again", account.name)
+        };
+        let expired_item = MenuItem::with_id(
+            app,
+            format!("sign_in_again:{}", account.id),
+            label,
+            true,
+            None::<&str>,
+        )
+        .map_err(|e| e.to_string())?;
+        menu.append(&expired_item).map_err(|e| e.to_string())?;
+    }
+
+    // Show when background polling is backing off or paused after failures
+    if let Some(label) = app
+        .try_state::<copilot_tracker::PollingHealth>()
+        .and_then(|health| polling_status_label(&health.status()))
+    {
+        let polling_item = MenuItem::with_id(app, "polling_status", label, false, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        menu.append(&polling_item).map_err(|e| e.to_string())?;
+    }
+
     menu.append(&PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?)
         .map_err(|e| e.to_string())?;
 
@@ -610,12 +783,16 @@ fn rebuild_tray_menu(app: &AppHandle, update: Option<&UpdateInfo>) -> Result<(),
 async fn show_auth_window(
     app: AppHandle,
     state: tauri::State<'_, AuthManagerState>,
+    account: Option<String>,
 ) -> Result<bool, String> {
     let mut auth_manager = state
         .auth_manager
         .lock()
         .map_err(|e| format!("Failed to acquire auth manager lock: {}", e))?;
-    auth_manager.show_auth_window(&app)?;
+    let account = account.unwrap_or_else(|| copilot_tracker::DEFAULT_ACCOUNT_ID.to_string());
+    // Only known accounts get a webview profile and a store for the sign-in
+    copilot_tracker::with_account_store(&app, &account, |_| ())?;
+    auth_manager.show_auth_window(&app, &account)?;
     Ok(true)
 }
 
@@ -654,12 +831,18 @@ async fn check_auth_status(
     let customer_id = store.get_customer_id();
 
     let is_authenticated = customer_id.is_some();
-    let state_str = if is_authenticated { "authenticated" } else { "unauthenticated" };
+    let session_expired = is_authenticated && store.is_session_expired();
+    let state_str = match (is_authenticated, session_expired) {
+        (true, true) => "expired",
+        (true, false) => "authenticated",
+        (false, _) => "unauthenticated",
+    };
     let _ = app.emit("auth:state-changed", state_str);
 
     Ok(copilot_tracker::AuthState {
         is_authenticated,
         customer_id,
+        session_expired,
     })
 }
 
@@ -685,7 +868,8 @@ async fn fetch_usage(
             &history,
             summary.used,
             summary.limit,
-            settings.prediction_period,
+            &settings,
+            &SystemClock,
         );
         let payload = copilot_tracker::UsagePayload {
             summary: summary.clone(),
@@ -731,24 +915,11 @@ fn get_cached_usage_data(
         return Ok(None);
     }
     
-    let remaining = limit.saturating_sub(used);
-    let percentage = if limit > 0 {
-        (used as f32 / limit as f32) * 100.0
-    } else {
-        0.0
-    };
-    
-    let summary = copilot_tracker::UsageSummary {
-        used,
-        limit,
-        remaining,
-        percentage,
-        timestamp: chrono::Utc::now().timestamp(),
-    };
+    let summary = UsageManager::new().cached_summary(&store);
     
     let history = UsageManager::get_cached_history(&app);
     let settings = store.get_settings();
-    let prediction = UsageManager::predict_usage_from_history(&history, used, limit, settings.prediction_period);
+    let prediction = UsageManager::predict_usage_from_history(&history, used, limit, &settings, &SystemClock);
     
     Ok(Some(copilot_tracker::UsagePayload {
         summary,
@@ -757,10 +928,59 @@ fn get_cached_usage_data(
     }))
 }
 
+/// Intra-day usage snapshots, optionally only those recorded since `since` (unix seconds)
+#[tauri::command]
+fn get_usage_snapshots(
+    app: AppHandle,
+    since: Option<i64>,
+) -> Result<Vec<copilot_tracker::UsageSnapshot>, String> {
+    let store = app.state::<StoreManager>();
+    Ok(store.get_usage_snapshots(since))
+}
+
+/// Usage for a specific period and grouping, fetched on demand
+/// Live usage is left alone; fetching the previous cycle stores it for comparison
+#[tauri::command]
+async fn fetch_usage_period(
+    app: AppHandle,
+    query: copilot_tracker::UsageQuery,
+) -> Result<copilot_tracker::PeriodUsage, String> {
+    let store = app.state::<StoreManager>();
+    let source = copilot_tracker::configured_source(&app);
+    UsageManager::new().fetch_period(&store, source.as_ref(), &query).await
+}
+
+/// Current billing cycle next to the stored previous one
+#[tauri::command]
+fn get_cycle_comparison(app: AppHandle) -> Result<copilot_tracker::CycleComparison, String> {
+    let store = app.state::<StoreManager>();
+    Ok(UsageManager::new().cycle_comparison(&store))
+}
+
+/// Latest usage of each selected billing entity, personal account first
+#[tauri::command]
+fn get_entity_usage(app: AppHandle) -> Result<Vec<copilot_tracker::UsageCache>, String> {
+    Ok(app.state::<StoreManager>().get_usage_caches())
+}
+
 // ============================================================================
 // IPC Commands - Settings
 // ============================================================================
 
+/// Choose the organizations and enterprises to track by key (see `BillingEntity::key`)
+/// Newly selected entities are fetched right away in the background
+#[tauri::command]
+fn set_selected_billing_entities(app: AppHandle, keys: Vec<String>) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    store.set_selected_billing_entities(keys)?;
+    let _ = app.emit("settings:changed", store.get_settings());
+
+    tauri::async_runtime::spawn(async move {
+        UsageManager::new().refresh_entities(&app).await;
+    });
+    Ok(())
+}
+
 #[tauri::command]
 fn get_settings(
     app: AppHandle,
@@ -821,6 +1041,7 @@ fn reset_settings(app: AppHandle) -> Result<copilot_tracker::AppSettings, String
     
     let store = app.state::<StoreManager>();
     let defaults = store.reset_settings()?;
+    app.state::<AccountStores>().delete_all()?;
     
     log::info!("Store reset complete, customer_id is now: {:?}", store.get_customer_id());
     
@@ -842,13 +1063,7 @@ fn reset_settings(app: AppHandle) -> Result<copilot_tracker::AppSettings, String
     let (used, limit) = store.get_usage();
     log::info!("Reset usage values: used={}, limit={}", used, limit);
     
-    let summary = copilot_tracker::UsageSummary {
-        used,
-        limit,
-        remaining: limit.saturating_sub(used),
-        percentage: if limit > 0 { (used as f32 / limit as f32) * 100.0 } else { 0.0 },
-        timestamp: chrono::Utc::now().timestamp(),
-    };
+    let summary = UsageManager::new().cached_summary(&store);
     let _ = app.emit("usage:updated", &summary);
     log::info!("Emitted usage:updated to reset tray icon");
 
@@ -865,15 +1080,29 @@ fn reset_settings(app: AppHandle) -> Result<copilot_tracker::AppSettings, String
     Ok(defaults)
 }
 
+/// Sign out `account` (the default account when omitted); its usage and history are kept
 #[tauri::command]
-async fn logout(app: AppHandle) -> Result<(), String> {
+async fn logout(app: AppHandle, account: Option<String>) -> Result<(), String> {
+    let account = account.unwrap_or_else(|| copilot_tracker::DEFAULT_ACCOUNT_ID.to_string());
+    if account != copilot_tracker::DEFAULT_ACCOUNT_ID {
+        copilot_tracker::with_account_store(&app, &account, |store| store.clear_auth())??;
+        log::info!("[Logout] Signed out account {}", account);
+        refresh_tray(&app);
+        return Ok(());
+    }
+
     let store = app.state::<StoreManager>();
     store.clear_auth()?;
     
-    // Stop background polling when user logs out
-    let polling_state = app.state::<PollingState>();
-    polling_state.stop_polling();
-    log::info!("[Logout] Background polling stopped");
+    // Stop background polling when no account is left signed in
+    let others_signed_in = copilot_tracker::account_usages(&app)
+        .iter()
+        .any(|a| a.is_authenticated);
+    if !others_signed_in {
+        let polling_state = app.state::<PollingState>();
+        polling_state.stop_polling();
+        log::info!("[Logout] Background polling stopped");
+    }
     
     // Emit event to frontend
     let _ = app.emit("auth:state-changed", "unauthenticated");
@@ -881,6 +1110,77 @@ async fn logout(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Redraw the tray icon and menu from the stores
+fn refresh_tray(app: &AppHandle) {
+    let _ = update_tray_icon_from_store(app);
+    let update_state = app.state::<UpdateState>();
+    let latest = update_state.latest.lock().unwrap();
+    let _ = rebuild_tray_menu(app, latest.as_ref());
+}
+
+// ============================================================================
+// IPC Commands - Accounts
+// ============================================================================
+
+/// Every account with its latest usage, default account first
+#[tauri::command]
+fn list_accounts(app: AppHandle) -> Result<Vec<copilot_tracker::AccountUsage>, String> {
+    Ok(copilot_tracker::account_usages(&app))
+}
+
+/// Add an account profile; sign it in with `show_auth_window`
+#[tauri::command]
+fn add_account(app: AppHandle, name: String) -> Result<copilot_tracker::AccountProfile, String> {
+    if !copilot_tracker::supports_isolated_webviews() {
+        return Err(copilot_tracker::ISOLATION_UNSUPPORTED.to_string());
+    }
+    let store = app.state::<StoreManager>();
+    let profile = store.add_account(name)?;
+    let _ = app.emit("settings:changed", store.get_settings());
+    refresh_tray(&app);
+    Ok(profile)
+}
+
+#[tauri::command]
+fn rename_account(app: AppHandle, id: String, name: String) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    store.rename_account(&id, name)?;
+    let _ = app.emit("settings:changed", store.get_settings());
+    refresh_tray(&app);
+    Ok(())
+}
+
+/// Remove an account profile and delete its usage, history and settings
+#[tauri::command]
+fn remove_account(app: AppHandle, id: String) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    store.remove_account(&id)?;
+    app.state::<AccountStores>().delete(&id)?;
+    let _ = app.emit("settings:changed", store.get_settings());
+    refresh_tray(&app);
+    Ok(())
+}
+
+/// Choose the account shown in the tray
+#[tauri::command]
+fn set_displayed_account(app: AppHandle, id: String) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    store.set_displayed_account(&id)?;
+    let _ = app.emit("settings:changed", store.get_settings());
+    refresh_tray(&app);
+    Ok(())
+}
+
+/// Show the usage summed over all signed-in accounts in the tray
+#[tauri::command]
+fn set_aggregate_tray_text(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    store.update_settings(|s| s.aggregate_tray_text = enabled)?;
+    let _ = app.emit("settings:changed", store.get_settings());
+    refresh_tray(&app);
+    Ok(())
+}
+
 #[tauri::command]
 fn set_launch_at_login(
     app: AppHandle,
@@ -909,6 +1209,21 @@ fn set_launch_at_login(
     Ok(())
 }
 
+/// Set the day of the month and time zone the quota resets in
+#[tauri::command]
+fn set_billing_cycle(app: AppHandle, anchor_day: u32, time_zone: String) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    store.set_billing_cycle(anchor_day, time_zone)?;
+
+    // Daily budget, days remaining and the forecast all depend on the cycle
+    let update_state = app.state::<UpdateState>();
+    let latest = update_state.latest.lock().unwrap();
+    let _ = rebuild_tray_menu(&app, latest.as_ref());
+    let _ = app.emit("settings:changed", store.get_settings());
+
+    Ok(())
+}
+
 #[tauri::command]
 fn hide_main_window(app: AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("main") {
@@ -1149,9 +1464,11 @@ async fn set_widget_enabled(app: AppHandle, enabled: bool) -> Result<(), String>
 // ============================================================================
 
 /// Helper function to process release data and emit appropriate events
+/// Accepts a GitHub releases listing (or a single release) and picks the newest
+/// release eligible for the configured update channel
 fn process_release_data(
     app: &AppHandle,
-    release: serde_json::Value,
+    releases: serde_json::Value,
     send_status: &dyn Fn(&str, Option<&str>),
 ) -> Result<(), String> {
     let send_status = send_status;
@@ -1165,21 +1482,7 @@ fn process_release_data(
     let store = app.state::<StoreManager>();
     let _ = store.set_last_update_check_timestamp(now.timestamp());
 
-    let tag_name = release
-        .get("tag_name")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
-    let latest_version = tag_name.trim_start_matches('v');
     let current_version = app.package_info().version.to_string();
-
-    let latest = match semver::Version::parse(&latest_version) {
-        Ok(version) => version,
-        Err(_) => {
-            send_status("error", Some("Invalid version format"));
-            return Err("Invalid version format".to_string());
-        }
-    };
     let current = match semver::Version::parse(&current_version) {
         Ok(version) => version,
         Err(_) => {
@@ -1188,16 +1491,61 @@ fn process_release_data(
         }
     };
 
-    if latest > current {
+    let channel = store.get_update_channel();
+    let candidates = match releases {
+        serde_json::Value::Array(list) => list,
+        single => vec![single],
+    };
+    let selected = copilot_tracker::select_release_for_channel(&candidates, &channel);
+    log::info!(
+        "[Update] Channel '{}': {} releases listed, newest eligible: {}",
+        channel,
+        candidates.len(),
+        selected
+            .as_ref()
+            .and_then(|r| r.get("tag_name"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("none")
+    );
+
+    // Only offer the selected release if it is newer than the running version
+    let newer_release = selected.filter(|release| {
+        release
+            .get("tag_name")
+            .and_then(|v| v.as_str())
+            .and_then(copilot_tracker::parse_release_version)
+            .map(|latest| latest > current)
+            .unwrap_or(false)
+    });
+
+    if let Some(release) = newer_release {
+        let tag_name = release
+            .get("tag_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
         let assets = release
             .get("assets")
             .and_then(|v| v.as_array())
             .cloned()
             .unwrap_or_default();
-        let download_url = assets
-            .iter()
-            .find_map(|asset| asset.get("browser_download_url").and_then(|v| v.as_str()))
-            .map(|s| s.to_string());
+        let target = copilot_tracker::UpdateTarget::current();
+        let candidates = copilot_tracker::rank_release_assets(&assets, &target);
+        let download_asset = candidates.first().cloned();
+        match &download_asset {
+            Some(asset) => log::info!(
+                "[Update] Best asset for {}/{}: {} ({} candidates)",
+                target.os,
+                target.arch,
+                asset.name,
+                candidates.len()
+            ),
+            None => log::warn!(
+                "[Update] No release asset matches {}/{}, falling back to release page",
+                target.os,
+                target.arch
+            ),
+        }
 
         let info = UpdateInfo {
             version: tag_name,
@@ -1206,7 +1554,9 @@ fn process_release_data(
                 .and_then(|v| v.as_str())
                 .unwrap_or("https://github.com/bizzkoot/copilot-tracker/releases")
                 .to_string(),
-            download_url,
+            download_url: download_asset.as_ref().map(|asset| asset.download_url.clone()),
+            download_asset,
+            assets: candidates,
             release_name: release.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()),
             release_notes: release.get("body").and_then(|v| v.as_str()).map(|s| s.to_string()),
             release_date: release.get("published_at").and_then(|v| v.as_str()).map(|s| s.to_string()),
@@ -1249,6 +1599,194 @@ fn process_release_data(
     Ok(())
 }
 
+#[tauri::command]
+fn set_update_channel(app: AppHandle, channel: String) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    store.set_update_channel(channel)?;
+
+    // A previously offered release may not belong to the new channel
+    let update_state = app.state::<UpdateState>();
+    *update_state.latest.lock().unwrap() = None;
+    let _ = rebuild_tray_menu(&app, None);
+    let _ = app.emit("settings:changed", store.get_settings());
+
+    Ok(())
+}
+
+/// Choose where usage comes from (see `USAGE_SOURCES`)
+/// The REST API needs a token saved with `set_github_token`. Switching to it fetches first and
+/// saves the choice only once that succeeds, so a failing token keeps the current source and
+/// a good one signs the account in without the webview
+#[tauri::command]
+async fn set_usage_source(app: AppHandle, source: String) -> Result<(), String> {
+    let store = app.state::<StoreManager>();
+    if source != "rest" {
+        store.set_usage_source(source)?;
+        let _ = app.emit("settings:changed", store.get_settings());
+        return Ok(());
+    }
+
+    let token = app
+        .state::<CredentialManager>()
+        .github_token()
+        .ok_or("The REST usage source needs a GitHub token")?;
+    let result = copilot_tracker::RestUsageSource::new(token, &store.get_settings())
+        .fetch(&copilot_tracker::UsageQuery::default())
+        .await?;
+    store.set_usage_source(source)?;
+    let _ = app.emit("settings:changed", store.get_settings());
+
+    let usage_manager = UsageManager::new();
+    if let Some(outcome) = usage_manager.apply_extraction(&store, result) {
+        usage_manager.publish(&app, &outcome);
+    }
+    let _ = app.emit("auth:state-changed", "authenticated");
+
+    Ok(())
+}
+
+/// Check a GitHub token and keep it in the credential store
+/// Tokens that cannot read the usage report are refused, since the REST source could not use them
+#[tauri::command]
+async fn set_github_token(
+    app: AppHandle,
+    token: String,
+) -> Result<copilot_tracker::TokenInfo, String> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Err("GitHub token is empty".to_string());
+    }
+
+    let info = copilot_tracker::validate_token(token).await?;
+    if !info.can_read_usage {
+        return Err(format!(
+            "This token for {} cannot read premium request usage. Fine-grained tokens need the \"Plan\" user permission (read-only)",
+            info.login
+        ));
+    }
+
+    let credentials = app.state::<CredentialManager>();
+    credentials.set_github_token(token)?;
+    log::info!(
+        "[Credentials] Saved {} token for {} in the {}",
+        info.kind,
+        info.login,
+        credentials.backend()
+    );
+    Ok(info)
+}
+
+/// Check the stored GitHub token against the API, e.g. to show its expiry
+#[tauri::command]
+async fn validate_github_token(app: AppHandle) -> Result<copilot_tracker::TokenInfo, String> {
+    let token = app
+        .state::<CredentialManager>()
+        .github_token()
+        .ok_or("No GitHub token is stored")?;
+    copilot_tracker::validate_token(&token).await
+}
+
+/// Delete the stored GitHub token and ask GitHub to revoke it
+/// Returns whether GitHub revoked it; the local copy is deleted either way and the REST
+/// source falls back to the webview
+#[tauri::command]
+async fn revoke_github_token(app: AppHandle) -> Result<bool, String> {
+    let credentials = app.state::<CredentialManager>();
+    let Some(token) = credentials.github_token() else {
+        return Ok(false);
+    };
+    credentials.delete_github_token()?;
+
+    let store = app.state::<StoreManager>();
+    if store.get_settings().usage_source == "rest" {
+        store.set_usage_source("webview".to_string())?;
+        let _ = app.emit("settings:changed", store.get_settings());
+    }
+
+    match copilot_tracker::revoke_token(&token).await {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            log::warn!("[Credentials] Token deleted locally but not revoked: {}", e);
+            Ok(false)
+        }
+    }
+}
+
+/// Download the selected update installer, verify its SHA-256 and open it
+/// `asset_name` picks one of the platform candidates (defaults to the best match);
+/// `launch` opens the installer, otherwise it is revealed in the file manager
+#[tauri::command]
+async fn download_update(
+    app: AppHandle,
+    asset_name: Option<String>,
+    launch: Option<bool>,
+) -> Result<String, String> {
+    let info = app
+        .state::<UpdateState>()
+        .latest
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No update available to download")?;
+
+    let asset = match asset_name {
+        Some(name) => info
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .cloned()
+            .ok_or(format!("Asset {} is not available for this platform", name))?,
+        None => info
+            .download_asset
+            .clone()
+            .ok_or("No installer available for this platform")?,
+    };
+
+    let dest_dir = app.state::<StoreManager>().data_dir().join("updates");
+    log::info!("[Update] Downloading {} to {:?}", asset.name, dest_dir);
+
+    let progress_app = app.clone();
+    let progress_name = asset.name.clone();
+    let result = copilot_tracker::download_verified_asset(&asset, &dest_dir, move |downloaded, total| {
+        let percent = total
+            .filter(|t| *t > 0)
+            .map(|t| (downloaded as f32 / t as f32) * 100.0);
+        let _ = progress_app.emit(
+            "update:download-progress",
+            UpdateDownloadProgress {
+                asset_name: progress_name.clone(),
+                downloaded,
+                total,
+                percent,
+            },
+        );
+    })
+    .await;
+
+    let path = match result {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("[Update] Download failed: {}", e);
+            let _ = app.emit("update:download-failed", e.clone());
+            return Err(e);
+        }
+    };
+    let path_str = path.to_string_lossy().to_string();
+    let _ = app.emit("update:downloaded", path_str.clone());
+
+    if launch.unwrap_or(true) {
+        app.opener()
+            .open_path(path_str.clone(), None::<&str>)
+            .map_err(|e| format!("Failed to launch installer: {}", e))?;
+    } else {
+        app.opener()
+            .reveal_item_in_dir(&path)
+            .map_err(|e| format!("Failed to reveal installer: {}", e))?;
+    }
+
+    Ok(path_str)
+}
+
 #[tauri::command]
 async fn check_for_updates(app: AppHandle) -> Result<(), String> {
     let send_status = |status: &str, message: Option<&str>| {
@@ -1268,7 +1806,7 @@ async fn check_for_updates(app: AppHandle) -> Result<(), String> {
         Ok(release_json) => {
             // Successfully fetched via webview
             log::info!("[Update] Webview fetch succeeded");
-            let release = if release_json.get("success").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let releases = if release_json.get("success").and_then(|v| v.as_bool()).unwrap_or(false) {
                 release_json.get("data").cloned().unwrap_or(release_json)
             } else {
                 // Store last check time even on error
@@ -1296,7 +1834,7 @@ async fn check_for_updates(app: AppHandle) -> Result<(), String> {
                 let _ = rebuild_tray_menu(&app, None);
                 return Ok(());
             };
-            process_release_data(&app, release, &send_status)?;
+            process_release_data(&app, releases, &send_status)?;
         }
         Err(webview_err) => {
             log::warn!("[Update] Webview fetch failed: {}, trying reqwest fallback", webview_err);
@@ -1306,7 +1844,7 @@ async fn check_for_updates(app: AppHandle) -> Result<(), String> {
             
             let client = reqwest::Client::new();
             let response = client
-                .get(GITHUB_API_URL)
+                .get(GITHUB_RELEASES_API_URL)
                 .header("User-Agent", "Copilot-Tracker-App")
                 .send()
                 .await;
@@ -1338,7 +1876,7 @@ async fn check_for_updates(app: AppHandle) -> Result<(), String> {
                         return Ok(());
                     }
                     
-                    let release = match resp.json().await {
+                    let releases = match resp.json().await {
                         Ok(value) => {
                             log::info!("[Update] Reqwest fallback succeeded");
                             value
@@ -1371,7 +1909,7 @@ async fn check_for_updates(app: AppHandle) -> Result<(), String> {
                         }
                     };
                     
-                    process_release_data(&app, release, &send_status)?;
+                    process_release_data(&app, releases, &send_status)?;
                 }
                 Err(err) => {
                     log::error!("[Update] Both webview and reqwest failed. Reqwest error: {}", err);
@@ -1421,6 +1959,11 @@ fn update_tray_usage(
     limit: u32,
 ) -> Result<(), String> {
     let store = app.state::<StoreManager>();
+    let settings = store.get_settings();
+    // The frontend only knows the default account
+    if settings.aggregate_tray_text || settings.displayed_account_id() != copilot_tracker::DEFAULT_ACCOUNT_ID {
+        return update_tray_icon_from_store(&app);
+    }
     let format = store.get_tray_icon_format();
     update_tray_icon(&app, &state, used, limit, &format)
 }
@@ -1476,15 +2019,20 @@ fn main() {
 
     // Initialize StoreManager BEFORE the builder runs
     // This ensures state is available for plugins and early lifecycle events
+    let credentials = CredentialManager::new(&app_dir);
+    let account_stores = AccountStores::new(app_dir.clone());
     let store_manager = StoreManager::new(app_dir).expect("Failed to initialize StoreManager");
 
     tauri::Builder::default()
         // Manage state (CRITICAL FIX: StoreManager managed here, not in setup)
         .manage(store_manager)
+        .manage(credentials)
+        .manage(account_stores)
         .manage(tray_state)
         .manage(auth_manager_state)
         .manage(UpdateState::default())
         .manage(PollingState::new())
+        .manage(copilot_tracker::PollingHealth::new())
         // Register plugins
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_http::init())
@@ -1509,11 +2057,23 @@ fn main() {
             predict_eom_usage,
             days_until_limit,
             get_cached_usage_data,
+            get_usage_snapshots,
+            fetch_usage_period,
+            get_cycle_comparison,
+            get_entity_usage,
+            set_selected_billing_entities,
+            list_accounts,
+            add_account,
+            rename_account,
+            remove_account,
+            set_displayed_account,
+            set_aggregate_tray_text,
             // Settings commands
             get_settings,
             update_settings,
             reset_settings,
             set_launch_at_login,
+            set_billing_cycle,
             // Tray commands
             update_tray_usage,
             // Widget commands
@@ -1532,6 +2092,12 @@ fn main() {
             hide_main_window,
             open_external_url,
             check_for_updates,
+            set_update_channel,
+            set_usage_source,
+            set_github_token,
+            validate_github_token,
+            revoke_github_token,
+            download_update,
         ])
         // Setup application
         .setup(move |app| {
@@ -1686,6 +2252,20 @@ fn main() {
                         let _ = set_launch_at_login(app.clone(), enabled);
                         let _ = app.emit("settings:changed", store.get_settings());
                     }
+                    id if id.starts_with("sign_in_again:") => {
+                        let auth_state = app.state::<AuthManagerState>();
+                        let mut auth_manager = auth_state.auth_manager.lock().unwrap();
+                        if let Err(e) = auth_manager.show_auth_window(app, &id["sign_in_again:".len()..]) {
+                            log::error!("Failed to open sign-in window: {}", e);
+                        }
+                    }
+                    id if id.starts_with("account:") => {
+                        let _ = set_displayed_account(app.clone(), id["account:".len()..].to_string());
+                    }
+                    "aggregate_tray_text" => {
+                        let enabled = !app.state::<StoreManager>().get_settings().aggregate_tray_text;
+                        let _ = set_aggregate_tray_text(app.clone(), enabled);
+                    }
                     id if id.starts_with("prediction_period:") => {
                         if let Ok(value) = id.split(':').nth(1).unwrap_or("0").parse::<u32>() {
                             let store = app.state::<StoreManager>();
@@ -1777,6 +2357,33 @@ fn main() {
                 log::info!("[TrayListener] Tray icon and menu updated successfully");
             });
 
+            // Other accounts update the tray when it shows them or their total
+            let accounts_handle = app_handle.clone();
+            app_handle.listen("accounts:updated", move |_| {
+                refresh_tray(&accounts_handle);
+            });
+
+            // Data goes stale without any event; recheck it so the tray marks it, and keep
+            // the age in the menu current while it is stale
+            let staleness_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut was_stale = false;
+                loop {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(STALENESS_CHECK_SECS)).await;
+                    let stale = tray_is_stale(&staleness_handle);
+                    if stale || was_stale {
+                        refresh_tray(&staleness_handle);
+                    }
+                    was_stale = stale;
+                }
+            });
+
+            // Backoff and circuit changes of background polling show in the tray menu
+            let polling_handle = app_handle.clone();
+            app_handle.listen("polling:status", move |_| {
+                refresh_tray(&polling_handle);
+            });
+
             // Prevent app from quitting when main window is closed (hide instead)
             let main_window = app.get_webview_window("main").ok_or("Main window not found")?;
             let app_handle_close = app.handle().clone();
@@ -1836,19 +2443,7 @@ fn main() {
                     
                     log::info!("About to emit startup data: used={}, limit={}", used, limit);
                     
-                    let remaining = limit.saturating_sub(used);
-                    let percentage = if limit > 0 {
-                        (used as f32 / limit as f32) * 100.0
-                    } else {
-                        0.0
-                    };
-                    let summary = copilot_tracker::UsageSummary {
-                        used,
-                        limit,
-                        remaining,
-                        percentage,
-                        timestamp: chrono::Utc::now().timestamp(),
-                    };
+                    let summary = UsageManager::new().cached_summary(&store);
                     
                     let history = UsageManager::get_cached_history(&app_handle_for_emit);
                     let store = app_handle_for_emit.state::<StoreManager>();
@@ -1857,7 +2452,8 @@ fn main() {
                         &history,
                         used,
                         limit,
-                        settings.prediction_period,
+                        &settings,
+                        &SystemClock,
                     );
                     
                     log::info!("History entries: {}", history.len());
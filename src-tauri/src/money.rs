@@ -0,0 +1,197 @@
+/// Amount parsed from a billing cell, e.g. "$1,024.50" or "(€3,00)"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Money {
+    pub amount: f64,
+    /// ISO 4217 code when the cell names or implies one ("$" is read as USD)
+    pub currency: Option<String>,
+}
+
+/// Characters used only to group digits
+const GROUP_SEPARATORS: &[char] = &[' ', '\'', '\u{a0}', '\u{202f}', '\u{2009}', '_'];
+
+fn currency_for_symbol(symbol: char) -> Option<&'static str> {
+    match symbol {
+        '$' => Some("USD"),
+        '€' => Some("EUR"),
+        '£' => Some("GBP"),
+        '¥' => Some("JPY"),
+        '₹' => Some("INR"),
+        '₩' => Some("KRW"),
+        _ => None,
+    }
+}
+
+/// Parse a money cell
+/// Handles currency symbols and codes on either side ("$3.00", "3,00 €", "USD 3.00", "CA$3.00"),
+/// thousands separators in either convention ("1,024.50", "1.024,50", "1 024,50"),
+/// and credits written as "-$3.00", "$-3.00", "−3.00" or "($3.00)"
+pub fn parse_money(text: &str) -> Option<Money> {
+    let mut text = text.trim();
+    let mut negative = false;
+    if let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        negative = true;
+        text = inner.trim();
+    }
+
+    let mut number = String::new();
+    let mut letters = String::new();
+    let mut symbol = None;
+    for c in text.chars() {
+        match c {
+            '0'..='9' | '.' | ',' => number.push(c),
+            c if GROUP_SEPARATORS.contains(&c) => {}
+            '-' | '\u{2212}' if number.is_empty() => negative = true,
+            '+' if number.is_empty() => {}
+            c if c.is_ascii_alphabetic() => letters.push(c.to_ascii_uppercase()),
+            c => match currency_for_symbol(c) {
+                Some(_) if symbol.is_none() => symbol = Some(c),
+                _ => return None,
+            },
+        }
+    }
+
+    let currency = match (letters.as_str(), symbol) {
+        ("", None) => None,
+        ("", Some(symbol)) => currency_for_symbol(symbol).map(str::to_string),
+        // "CA$", "AU$", "US$"
+        (prefix, Some('$')) if prefix.len() == 2 => Some(format!("{}D", prefix)),
+        (code, None) if code.len() == 3 => Some(code.to_string()),
+        _ => return None,
+    };
+
+    let amount = normalize_decimal(&number)?.parse::<f64>().ok()?;
+    Some(Money {
+        amount: if negative { -amount } else { amount },
+        currency,
+    })
+}
+
+/// Rewrite digits with '.' and ',' separators as a plain decimal number
+/// With both present the last one is the decimal point; a lone ',' followed by exactly
+/// three digits groups thousands, any other single ',' or '.' is the decimal point
+fn normalize_decimal(number: &str) -> Option<String> {
+    let number = number.trim_end_matches(['.', ',']);
+    if number.is_empty() {
+        return None;
+    }
+
+    let last_dot = number.rfind('.');
+    let last_comma = number.rfind(',');
+    let decimal = match (last_dot, last_comma) {
+        (Some(dot), Some(comma)) => Some(dot.max(comma)),
+        (Some(dot), None) if number.matches('.').count() == 1 => Some(dot),
+        (None, Some(comma)) if number.matches(',').count() == 1 && number.len() - comma != 4 => {
+            Some(comma)
+        }
+        _ => None,
+    };
+
+    let mut normalized = String::with_capacity(number.len());
+    for (index, c) in number.char_indices() {
+        match c {
+            '.' | ',' if Some(index) == decimal => normalized.push('.'),
+            '.' | ',' => {}
+            c => normalized.push(c),
+        }
+    }
+    Some(normalized)
+}
+
+/// Parse a request count such as "1,204", "1.204" or "1 204"
+/// Counts are whole numbers, so '.' and ',' are only accepted as thousands separators
+pub fn parse_count(text: &str) -> Option<u32> {
+    let text = text.trim();
+    let groups: Vec<&str> = text
+        .split(|c: char| c == ',' || c == '.' || GROUP_SEPARATORS.contains(&c))
+        .collect();
+
+    let (first, rest) = groups.split_first()?;
+    let digits = groups
+        .iter()
+        .all(|g| !g.is_empty() && g.bytes().all(|b| b.is_ascii_digit()));
+    let grouped = rest.is_empty() || (first.len() <= 3 && rest.iter().all(|g| g.len() == 3));
+    if !digits || !grouped {
+        return None;
+    }
+
+    groups.concat().parse::<u32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: f64, currency: Option<&str>) -> Option<Money> {
+        Some(Money {
+            amount,
+            currency: currency.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn parses_plain_dollar_amounts() {
+        assert_eq!(parse_money("$0.00"), money(0.0, Some("USD")));
+        assert_eq!(parse_money(" $12.20 "), money(12.2, Some("USD")));
+        assert_eq!(parse_money("0.04"), money(0.04, None));
+        assert_eq!(parse_money("$.50"), money(0.5, Some("USD")));
+    }
+
+    #[test]
+    fn parses_thousands_separators() {
+        assert_eq!(parse_money("$1,024.50"), money(1024.5, Some("USD")));
+        assert_eq!(parse_money("$1,234"), money(1234.0, Some("USD")));
+        assert_eq!(parse_money("1.024,50 €"), money(1024.5, Some("EUR")));
+        assert_eq!(parse_money("1 024,50 €"), money(1024.5, Some("EUR")));
+        assert_eq!(parse_money("1\u{202f}024,50\u{a0}€"), money(1024.5, Some("EUR")));
+        assert_eq!(parse_money("£1'234.5"), money(1234.5, Some("GBP")));
+        assert_eq!(parse_money("12,50 €"), money(12.5, Some("EUR")));
+    }
+
+    #[test]
+    fn parses_credits() {
+        assert_eq!(parse_money("-$3.00"), money(-3.0, Some("USD")));
+        assert_eq!(parse_money("$-3.00"), money(-3.0, Some("USD")));
+        assert_eq!(parse_money("\u{2212}3.00"), money(-3.0, None));
+        assert_eq!(parse_money("($3.00)"), money(-3.0, Some("USD")));
+        assert_eq!(parse_money("(1,024.50 EUR)"), money(-1024.5, Some("EUR")));
+    }
+
+    #[test]
+    fn parses_currency_codes() {
+        assert_eq!(parse_money("USD 3.00"), money(3.0, Some("USD")));
+        assert_eq!(parse_money("3.00 eur"), money(3.0, Some("EUR")));
+        assert_eq!(parse_money("CA$3.00"), money(3.0, Some("CAD")));
+        assert_eq!(parse_money("US$ 1,000"), money(1000.0, Some("USD")));
+        assert_eq!(parse_money("¥1,200"), money(1200.0, Some("JPY")));
+    }
+
+    #[test]
+    fn rejects_non_amounts() {
+        assert_eq!(parse_money(""), None);
+        assert_eq!(parse_money("$"), None);
+        assert_eq!(parse_money("N/A"), None);
+        assert_eq!(parse_money("Included"), None);
+        assert_eq!(parse_money("$3.00 - $4.00"), None);
+        assert_eq!(parse_money("$€3"), None);
+    }
+
+    #[test]
+    fn parses_counts() {
+        assert_eq!(parse_count("0"), Some(0));
+        assert_eq!(parse_count("42"), Some(42));
+        assert_eq!(parse_count("1,204"), Some(1204));
+        assert_eq!(parse_count("1.204"), Some(1204));
+        assert_eq!(parse_count("1 204 000"), Some(1_204_000));
+        assert_eq!(parse_count(" 300 "), Some(300));
+    }
+
+    #[test]
+    fn rejects_malformed_counts() {
+        assert_eq!(parse_count(""), None);
+        assert_eq!(parse_count("-5"), None);
+        assert_eq!(parse_count("12.5"), None);
+        assert_eq!(parse_count("1,20"), None);
+        assert_eq!(parse_count("1234,567"), None);
+        assert_eq!(parse_count("ten"), None);
+    }
+}
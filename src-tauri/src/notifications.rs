@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::accounts::{self, DEFAULT_ACCOUNT_ID};
+use crate::billing::BillingCycle;
+use crate::calendar::SystemClock;
+use crate::store::StoreManager;
+
+/// Thresholds that have already fired, scoped to a billing cycle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationState {
+    /// Billing cycle the fired thresholds belong to (e.g. "2026-02")
+    pub cycle_key: String,
+    /// Threshold percentages already notified in this cycle
+    pub fired_thresholds: Vec<u32>,
+}
+
+fn usage_percentage(used: u32, limit: u32) -> f32 {
+    if limit > 0 {
+        (used as f32 / limit as f32) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Returns the thresholds reached at `used` that have not fired yet in this cycle
+/// Compared against the fired set rather than the previous usage, so a threshold whose
+/// notification could not be shown is retried on the next check
+pub fn crossed_thresholds(used: u32, limit: u32, thresholds: &[u32], already_fired: &[u32]) -> Vec<u32> {
+    if limit == 0 {
+        return vec![];
+    }
+
+    let current_pct = usage_percentage(used, limit);
+
+    let mut crossed: Vec<u32> = thresholds
+        .iter()
+        .copied()
+        .filter(|t| current_pct >= *t as f32)
+        .filter(|t| !already_fired.contains(t))
+        .collect();
+    crossed.sort_unstable();
+    crossed.dedup();
+    crossed
+}
+
+/// Evaluate usage thresholds after a fetch and show a notification for new crossings
+/// Thresholds are persisted as fired only once the notification was shown, so each one
+/// alerts once per billing cycle
+pub fn check_usage_thresholds(app: &AppHandle, used: u32, limit: u32) {
+    let store = match app.try_state::<StoreManager>() {
+        Some(store) => store,
+        None => return,
+    };
+    let settings = store.get_settings();
+    if !settings.show_notifications {
+        return;
+    }
+
+    let cycle_key = BillingCycle::current(&settings, &SystemClock).key();
+    let mut state = store.get_notification_state();
+    if state.cycle_key != cycle_key {
+        log::info!(
+            "[Notifications] New billing cycle {} (was {:?}), resetting fired thresholds",
+            cycle_key,
+            state.cycle_key
+        );
+        state = NotificationState {
+            cycle_key,
+            fired_thresholds: vec![],
+        };
+    }
+
+    let crossed = crossed_thresholds(
+        used,
+        limit,
+        &settings.notification_thresholds,
+        &state.fired_thresholds,
+    );
+    // Only alert for the highest crossed threshold to avoid a burst of notifications
+    let highest = match crossed.last() {
+        Some(highest) => *highest,
+        None => return,
+    };
+
+    log::info!(
+        "[Notifications] Usage {}/{} crossed thresholds {:?}",
+        used,
+        limit,
+        crossed
+    );
+
+    let body = if highest >= 100 {
+        format!("You have used all {} premium requests for this cycle.", limit)
+    } else {
+        format!(
+            "You have used {}% of your premium requests ({} / {}).",
+            highest, used, limit
+        )
+    };
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("Copilot Usage Alert")
+        .body(body)
+        .show()
+    {
+        log::error!("[Notifications] Failed to show threshold notification: {}", e);
+        return;
+    }
+
+    state.fired_thresholds.extend(crossed);
+    state.fired_thresholds.sort_unstable();
+    state.fired_thresholds.dedup();
+    if let Err(e) = store.set_notification_state(state) {
+        log::error!("[Notifications] Failed to persist notification state: {}", e);
+    }
+}
+
+/// Ask the user to sign in again after the GitHub session expired
+/// Called once per expiry; the store remembers the expiry until the next sign-in
+pub fn notify_session_expired(app: &AppHandle, account: &str) {
+    let show = app
+        .try_state::<StoreManager>()
+        .is_some_and(|store| store.get_show_notifications());
+    if !show {
+        return;
+    }
+    let body = if account == DEFAULT_ACCOUNT_ID {
+        "Sign in again to keep tracking your Copilot usage.".to_string()
+    } else {
+        format!(
+            "Sign in to {} again to keep tracking its Copilot usage.",
+            accounts::account_name(app, account)
+        )
+    };
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("GitHub Session Expired")
+        .body(body)
+        .show()
+    {
+        log::error!("[Notifications] Failed to show session expiry notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: &[u32] = &[50, 75, 90, 100];
+
+    #[test]
+    fn reports_reached_thresholds_not_yet_fired() {
+        assert_eq!(crossed_thresholds(100, 300, THRESHOLDS, &[]), Vec::<u32>::new());
+        assert_eq!(crossed_thresholds(150, 300, THRESHOLDS, &[]), [50]);
+        assert_eq!(crossed_thresholds(280, 300, THRESHOLDS, &[50]), [75, 90]);
+        assert_eq!(crossed_thresholds(300, 300, THRESHOLDS, &[50, 75, 90]), [100]);
+        assert_eq!(crossed_thresholds(320, 300, &[90, 50, 90], &[]), [50, 90]);
+    }
+
+    #[test]
+    fn retries_thresholds_whose_notification_failed() {
+        // Usage stayed at 80% but 75 was never recorded as fired, so it is still due
+        assert_eq!(crossed_thresholds(240, 300, THRESHOLDS, &[50]), [75]);
+        assert_eq!(crossed_thresholds(240, 300, THRESHOLDS, &[50, 75]), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn ignores_unlimited_plans() {
+        assert_eq!(crossed_thresholds(500, 0, THRESHOLDS, &[]), Vec::<u32>::new());
+        assert_eq!(crossed_thresholds(0, 0, THRESHOLDS, &[]), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn stays_quiet_when_usage_drops_after_a_cycle_reset() {
+        // The new cycle starts with no fired thresholds and usage back near zero
+        assert_eq!(crossed_thresholds(3, 300, THRESHOLDS, &[]), Vec::<u32>::new());
+        // A drop within the cycle does not re-arm thresholds that already fired
+        assert_eq!(crossed_thresholds(160, 300, THRESHOLDS, &[50, 75]), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,162 @@
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `path` with `suffix` appended to the full file name (e.g. settings.json.bak)
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name: OsString = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Backup copy kept next to `path` by `write_atomic`
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, "bak")
+}
+
+/// Flush the directory entry so a completed rename survives power loss
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) {}
+
+/// Replace `path` with `contents` without ever leaving a truncated file behind
+/// Writes a temp file, fsyncs it and renames it over the target; the previous
+/// contents are kept as the `.bak` copy
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let tmp_path = with_suffix(path, "tmp");
+
+    let mut file = std::fs::File::create(&tmp_path)
+        .map_err(|e| format!("Failed to create temp file {}: {}", tmp_path.display(), e))?;
+    file.write_all(contents)
+        .map_err(|e| format!("Failed to write temp file {}: {}", tmp_path.display(), e))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync temp file {}: {}", tmp_path.display(), e))?;
+    drop(file);
+
+    if path.exists() {
+        if let Err(e) = std::fs::copy(path, backup_path(path)) {
+            log::warn!("Failed to rotate backup for {}: {}", path.display(), e);
+        }
+    }
+
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    sync_parent_dir(path);
+
+    Ok(())
+}
+
+/// Load `path` with `parse`, falling back to its `.bak` copy when the file is unreadable
+/// A corrupt file is moved aside to `.corrupt` so the next write does not rotate it into
+/// the backup. Returns `None` when neither copy can be loaded
+pub fn load_with_backup<T, F>(path: &Path, parse: F) -> Option<T>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    let primary_error = match std::fs::read_to_string(path) {
+        Ok(content) => match parse(&content) {
+            Ok(value) => return Some(value),
+            Err(e) => e,
+        },
+        Err(e) => format!("Failed to read {}: {}", path.display(), e),
+    };
+    log::error!("{}", primary_error);
+
+    let corrupt_path = with_suffix(path, "corrupt");
+    if let Err(e) = std::fs::rename(path, &corrupt_path) {
+        log::warn!("Failed to move aside corrupt {}: {}", path.display(), e);
+    } else {
+        log::warn!("Moved corrupt file to {}", corrupt_path.display());
+    }
+
+    let backup = backup_path(path);
+    if !backup.exists() {
+        return None;
+    }
+
+    match std::fs::read_to_string(&backup)
+        .map_err(|e| format!("Failed to read {}: {}", backup.display(), e))
+        .and_then(|content| parse(&content))
+    {
+        Ok(value) => {
+            log::warn!("Recovered {} from backup", path.display());
+            Some(value)
+        }
+        Err(e) => {
+            log::error!("Backup is unusable too: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn parse(content: &str) -> Result<u32, String> {
+        content.trim().parse().map_err(|e| format!("Failed to parse: {}", e))
+    }
+
+    #[test]
+    fn keeps_the_previous_contents_as_backup() {
+        let dir = TempDir::new("write");
+        let path = dir.0.join("settings.json");
+
+        write_atomic(&path, b"1").unwrap();
+        assert!(!backup_path(&path).exists());
+        write_atomic(&path, b"2").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "2");
+        assert_eq!(std::fs::read_to_string(backup_path(&path)).unwrap(), "1");
+        assert!(!with_suffix(&path, "tmp").exists());
+        assert_eq!(load_with_backup(&path, parse), Some(2));
+    }
+
+    #[test]
+    fn recovers_a_corrupt_file_from_backup() {
+        let dir = TempDir::new("recover");
+        let path = dir.0.join("settings.json");
+        write_atomic(&path, b"1").unwrap();
+        write_atomic(&path, b"2").unwrap();
+        std::fs::write(&path, "{ trunc").unwrap();
+
+        assert_eq!(load_with_backup(&path, parse), Some(1));
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(with_suffix(&path, "corrupt")).unwrap(), "{ trunc");
+
+        // The next write must not rotate the corrupt contents into the backup
+        write_atomic(&path, b"3").unwrap();
+        assert_eq!(std::fs::read_to_string(backup_path(&path)).unwrap(), "1");
+    }
+
+    #[test]
+    fn falls_back_to_backup_when_the_file_is_missing() {
+        let dir = TempDir::new("missing");
+        let path = dir.0.join("settings.json");
+        assert_eq!(load_with_backup(&path, parse), None);
+
+        std::fs::write(backup_path(&path), "7").unwrap();
+        assert_eq!(load_with_backup(&path, parse), Some(7));
+    }
+
+    #[test]
+    fn gives_up_when_both_copies_are_corrupt() {
+        let dir = TempDir::new("both-corrupt");
+        let path = dir.0.join("settings.json");
+        std::fs::write(&path, "x").unwrap();
+        std::fs::write(backup_path(&path), "y").unwrap();
+
+        assert_eq!(load_with_backup(&path, parse), None);
+        assert!(with_suffix(&path, "corrupt").exists());
+    }
+}
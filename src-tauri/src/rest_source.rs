@@ -0,0 +1,545 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::auth::{ExtractionResult, UsageData, UsageHistoryRow, UsageModelRow};
+use crate::billing_entity::BillingEntity;
+use crate::calendar::{Clock, SystemClock};
+use crate::store::AppSettings;
+use crate::usage_query::{UsageGrouping, UsagePeriod, UsageQuery};
+use crate::usage_source::{SourceFuture, UsageSource};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+const GITHUB_API_VERSION: &str = "2022-11-28";
+
+/// Currency of the amounts in GitHub's billing reports
+const REPORT_CURRENCY: &str = "USD";
+
+/// One line of a premium request usage report
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PremiumRequestItem {
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub gross_amount: f64,
+    #[serde(default)]
+    pub discount_quantity: f64,
+    #[serde(default)]
+    pub net_quantity: f64,
+    #[serde(default)]
+    pub net_amount: f64,
+}
+
+/// Daily reports of settled days, per report path
+/// GitHub may still amend today's and yesterday's reports, so only older days are kept
+type DayReports = BTreeMap<NaiveDate, Vec<PremiumRequestItem>>;
+static SETTLED_DAY_REPORTS: Mutex<BTreeMap<String, DayReports>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PremiumRequestReport {
+    #[serde(default)]
+    usage_items: Vec<PremiumRequestItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: u64,
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubOrganization {
+    login: String,
+}
+
+/// What a token may do, as reported by GitHub
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    pub login: String,
+    /// "fine-grained", "classic" or "other"
+    pub kind: String,
+    /// OAuth scopes of a classic token; fine-grained tokens do not report theirs
+    pub scopes: Vec<String>,
+    /// Whether the token may read the premium request usage report
+    pub can_read_usage: bool,
+    /// Expiry reported by GitHub, when the token has one
+    pub expires_at: Option<String>,
+}
+
+/// Kind of personal access token, from its prefix
+pub fn token_kind(token: &str) -> &'static str {
+    if token.starts_with("github_pat_") {
+        "fine-grained"
+    } else if token.starts_with("ghp_") {
+        "classic"
+    } else {
+        "other"
+    }
+}
+
+/// Scopes listed in an `X-OAuth-Scopes` header
+pub fn parse_scopes(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn api_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .user_agent("Copilot-Tracker-App")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// GET an API path with `token`; only a rejected token is turned into an error
+async fn api_get(
+    client: &reqwest::Client,
+    token: &str,
+    path: &str,
+    query: &[(&str, u32)],
+) -> Result<reqwest::Response, String> {
+    let response = client
+        .get(format!("{}{}", GITHUB_API_URL, path))
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+        .query(query)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach the GitHub API: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("GitHub rejected the token (401 Unauthorized)".to_string());
+    }
+    Ok(response)
+}
+
+async fn get_json<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    token: &str,
+    path: &str,
+    query: &[(&str, u32)],
+) -> Result<T, String> {
+    let response = api_get(client, token, path, query).await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("GitHub API request to {} failed: {}", path, status));
+    }
+    parse_json(response).await
+}
+
+async fn parse_json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, String> {
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read GitHub API response: {}", e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Failed to parse GitHub API response: {}", e))
+}
+
+/// Premium request usage report of `entity`; `login` names the personal account
+fn usage_path(entity: &BillingEntity, login: &str) -> String {
+    let owner = if entity.is_personal() {
+        format!("/users/{}", login)
+    } else {
+        entity.path_prefix()
+    };
+    format!("{}/settings/billing/premium_request/usage", owner)
+}
+
+/// Check a token against the API: who it belongs to and whether it can read usage
+/// Classic tokens report their scopes; for fine-grained ones (which need the "Plan" user
+/// permission) the usage report itself is probed
+pub async fn validate_token(token: &str) -> Result<TokenInfo, String> {
+    let client = api_client()?;
+    let response = api_get(&client, token, "/user", &[]).await?;
+    if !response.status().is_success() {
+        return Err(format!("GitHub API request to /user failed: {}", response.status()));
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let scopes = header("x-oauth-scopes").map(|h| parse_scopes(&h)).unwrap_or_default();
+    let expires_at = header("github-authentication-token-expiration");
+    let user: GitHubUser = parse_json(response).await?;
+
+    let probe = api_get(&client, token, &usage_path(&BillingEntity::personal(), &user.login), &[]).await?;
+    let can_read_usage = probe.status().is_success();
+    if !can_read_usage {
+        log::warn!("[RestUsage] Token for {} cannot read usage: {}", user.login, probe.status());
+    }
+
+    Ok(TokenInfo {
+        login: user.login,
+        kind: token_kind(token).to_string(),
+        scopes,
+        can_read_usage,
+        expires_at,
+    })
+}
+
+/// Ask GitHub to revoke `token` so it stops working everywhere, not only in this app
+pub async fn revoke_token(token: &str) -> Result<(), String> {
+    let response = api_client()?
+        .post(format!("{}/credentials/revoke", GITHUB_API_URL))
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+        .header("Content-Type", "application/json")
+        .body(serde_json::json!({ "credentials": [token] }).to_string())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach the GitHub API: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("GitHub did not revoke the token: {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Reads premium request usage from GitHub's billing REST API with a fine-grained personal
+/// access token, instead of scraping the billing page in a webview
+/// Totals come from the monthly report; the report has no per-day breakdown, so the daily
+/// history needs one request per day, of which settled days are cached
+pub struct RestUsageSource {
+    token: String,
+    settings: AppSettings,
+    entity: BillingEntity,
+    clock: Box<dyn Clock>,
+}
+
+impl RestUsageSource {
+    pub fn new(token: String, settings: &AppSettings) -> Self {
+        Self {
+            token,
+            settings: settings.clone(),
+            entity: BillingEntity::personal(),
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    /// Read the report of `entity` instead of the personal account
+    pub fn with_entity(mut self, entity: BillingEntity) -> Self {
+        self.entity = entity;
+        self
+    }
+
+    /// Daily reports of the days in `start..end`, oldest first
+    /// Settled days come from the cache; a day that fails is skipped, unless every one does
+    async fn day_reports(
+        &self,
+        client: &reqwest::Client,
+        path: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        today: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<PremiumRequestItem>)>, String> {
+        let cached = SETTLED_DAY_REPORTS
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut days = Vec::new();
+        let mut settled = Vec::new();
+        let mut last_error = None;
+        for date in start.iter_days().take_while(|date| *date < end) {
+            if let Some(items) = cached.get(&date) {
+                days.push((date, items.clone()));
+                continue;
+            }
+            let params = [("year", date.year() as u32), ("month", date.month()), ("day", date.day())];
+            match get_json::<PremiumRequestReport>(client, &self.token, path, &params).await {
+                Ok(report) => {
+                    if is_settled(date, today) {
+                        settled.push((date, report.usage_items.clone()));
+                    }
+                    days.push((date, report.usage_items));
+                }
+                Err(e) => {
+                    log::warn!("[RestUsage] Failed to read usage of {}: {}", date, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if days.is_empty() {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
+        if !settled.is_empty() {
+            SETTLED_DAY_REPORTS
+                .lock()
+                .unwrap()
+                .entry(path.to_string())
+                .or_default()
+                .extend(settled);
+        }
+        Ok(days)
+    }
+
+    /// Organizations the token can see, `None` when it may not list them
+    /// Enterprises have no REST listing; they are found by the webview source
+    async fn list_organizations(&self, client: &reqwest::Client) -> Option<Vec<BillingEntity>> {
+        match get_json::<Vec<GitHubOrganization>>(client, &self.token, "/user/orgs", &[]).await {
+            Ok(orgs) => Some(
+                orgs.iter()
+                    .map(|org| BillingEntity::organization(&org.login, None))
+                    .collect(),
+            ),
+            Err(e) => {
+                log::warn!("[RestUsage] Failed to list organizations: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl UsageSource for RestUsageSource {
+    fn fetch(&self, query: &UsageQuery) -> SourceFuture<'_> {
+        let query = *query;
+        Box::pin(async move {
+            let now = self.clock.now();
+            let (start, end) = query.date_range(now);
+            // Reports are kept in UTC days and end today
+            let end = end.min(now.date_naive() + Duration::days(1));
+
+            let client = api_client()?;
+            let user: GitHubUser = get_json(&client, &self.token, "/user", &[]).await?;
+            let path = usage_path(&self.entity, &user.login);
+
+            // Month periods span exactly one report month, which one request covers
+            let month = match query.period {
+                UsagePeriod::LastDays { .. } => None,
+                _ => {
+                    let params = [("year", start.year() as u32), ("month", start.month())];
+                    let report: PremiumRequestReport = get_json(&client, &self.token, &path, &params).await?;
+                    Some(report.usage_items)
+                }
+            };
+            let days = if month.is_none() || query.group == UsageGrouping::Day {
+                self.day_reports(&client, &path, start, end, now.date_naive()).await?
+            } else {
+                Vec::new()
+            };
+            log::info!(
+                "[RestUsage] Read {} days of premium request usage for {}",
+                days.len(),
+                if self.entity.is_personal() { user.login.clone() } else { self.entity.key() }
+            );
+
+            // The allowance is per user, so entities other than the personal account have none
+            if !self.entity.is_personal() {
+                return Ok(build_result(user.id, &days, month.as_deref(), 0, &query));
+            }
+            let limit = self.settings.premium_request_limit;
+            let mut result = build_result(user.id, &days, month.as_deref(), limit, &query);
+            result.billing_entities = self.list_organizations(&client).await;
+            Ok(result)
+        })
+    }
+}
+
+/// Whether GitHub no longer amends the report of `date`
+fn is_settled(date: NaiveDate, today: NaiveDate) -> bool {
+    date < today - Duration::days(1)
+}
+
+/// Extraction result for the daily reports in `days`
+/// Totals and model rows come from the monthly report `month` when there is one
+pub fn build_result(
+    user_id: u64,
+    days: &[(NaiveDate, Vec<PremiumRequestItem>)],
+    month: Option<&[PremiumRequestItem]>,
+    limit: u32,
+    query: &UsageQuery,
+) -> ExtractionResult {
+    let items: Vec<&PremiumRequestItem> = match month {
+        Some(items) => items.iter().collect(),
+        None => days.iter().flat_map(|(_, items)| items).collect(),
+    };
+    let usage_data = UsageData {
+        net_billed_amount: items.iter().map(|i| i.net_amount).sum(),
+        net_quantity: items.iter().map(|i| i.net_quantity).sum::<f64>().round() as u64,
+        discount_quantity: items.iter().map(|i| i.discount_quantity).sum::<f64>().round() as u64,
+        user_premium_request_entitlement: limit as u64,
+        filtered_user_premium_request_entitlement: limit as u64,
+    };
+
+    let (usage_history, usage_models) = match query.group {
+        UsageGrouping::Day => (
+            Some(days.iter().map(|(date, items)| day_row(*date, items)).collect()),
+            None,
+        ),
+        UsageGrouping::Model => (None, Some(model_rows(items))),
+    };
+
+    ExtractionResult {
+        customer_id: Some(user_id),
+        usage_data: Some(usage_data),
+        usage_history,
+        usage_models,
+        error: None,
+        schema_drift: None,
+        extractor_version: None,
+        billing_entities: None,
+        session_expired: false,
+    }
+}
+
+fn day_row(date: NaiveDate, items: &[PremiumRequestItem]) -> UsageHistoryRow {
+    let models = model_rows(items.iter());
+    UsageHistoryRow {
+        date: date.format("%Y-%m-%d").to_string(),
+        included_requests: models.iter().map(|m| m.included_requests).sum(),
+        billed_requests: models.iter().map(|m| m.billed_requests).sum(),
+        gross_amount: items.iter().map(|i| i.gross_amount).sum(),
+        billed_amount: items.iter().map(|i| i.net_amount).sum(),
+        currency: Some(REPORT_CURRENCY.to_string()),
+        models,
+    }
+}
+
+/// Items summed per model, in order of first appearance
+fn model_rows<'a>(items: impl IntoIterator<Item = &'a PremiumRequestItem>) -> Vec<UsageModelRow> {
+    let mut rows: Vec<UsageModelRow> = Vec::new();
+    for item in items {
+        let included = item.discount_quantity.round() as u32;
+        let billed = item.net_quantity.round() as u32;
+        match rows.iter_mut().find(|r| r.name == item.model) {
+            Some(row) => {
+                row.included_requests += included;
+                row.billed_requests += billed;
+                row.gross_amount += item.gross_amount;
+                row.billed_amount += item.net_amount;
+            }
+            None => rows.push(UsageModelRow {
+                name: item.model.clone(),
+                included_requests: included,
+                billed_requests: billed,
+                gross_amount: item.gross_amount,
+                billed_amount: item.net_amount,
+            }),
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage_query::UsagePeriod;
+
+    fn report(json: &str) -> Vec<PremiumRequestItem> {
+        serde_json::from_str::<PremiumRequestReport>(json).unwrap().usage_items
+    }
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn fixture_days() -> Vec<(NaiveDate, Vec<PremiumRequestItem>)> {
+        vec![
+            (date(9), report(include_str!("../tests/fixtures/premium_request_usage.json"))),
+            (date(10), report(r#"{ "timePeriod": { "year": 2026, "month": 3, "day": 10 }, "usageItems": [] }"#)),
+        ]
+    }
+
+    #[test]
+    fn maps_daily_reports_to_history() {
+        let result = build_result(7, &fixture_days(), None, 300, &UsageQuery::default());
+
+        assert_eq!(result.customer_id, Some(7));
+        let usage = result.usage_data.unwrap();
+        assert_eq!(usage.discount_quantity, 42);
+        assert_eq!(usage.net_quantity, 3);
+        assert_eq!(usage.user_premium_request_entitlement, 300);
+        assert!((usage.net_billed_amount - 0.12).abs() < 1e-9);
+
+        let history = result.usage_history.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].date, "2026-03-09");
+        assert_eq!(history[0].included_requests, 42);
+        assert_eq!(history[0].billed_requests, 3);
+        assert_eq!(history[0].currency.as_deref(), Some("USD"));
+        let models: Vec<&str> = history[0].models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(models, ["Claude Sonnet 4", "GPT-4.1"]);
+        assert_eq!(history[0].models[0].included_requests, 30);
+        assert_eq!(history[1].included_requests, 0);
+        assert!(result.usage_models.is_none());
+    }
+
+    #[test]
+    fn takes_totals_from_the_monthly_report() {
+        let month = report(r#"{ "usageItems": [
+            { "model": "o3", "discountQuantity": 250, "netQuantity": 10, "netAmount": 0.4 }
+        ] }"#);
+        let query = UsageQuery::new(UsagePeriod::CurrentCycle, UsageGrouping::Model);
+        let result = build_result(7, &[], Some(&month), 300, &query);
+
+        let usage = result.usage_data.unwrap();
+        assert_eq!(usage.discount_quantity, 250);
+        assert_eq!(usage.net_quantity, 10);
+        let models = result.usage_models.unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].included_requests, 250);
+
+        // Daily rows still come from the daily reports
+        let query = UsageQuery::default();
+        let result = build_result(7, &fixture_days(), Some(&month), 300, &query);
+        assert_eq!(result.usage_data.unwrap().discount_quantity, 250);
+        assert_eq!(result.usage_history.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn only_days_before_yesterday_are_settled() {
+        assert!(is_settled(date(8), date(10)));
+        assert!(!is_settled(date(9), date(10)));
+        assert!(!is_settled(date(10), date(10)));
+    }
+
+    #[test]
+    fn usage_paths_per_entity() {
+        let path = usage_path(&BillingEntity::personal(), "octocat");
+        assert_eq!(path, "/users/octocat/settings/billing/premium_request/usage");
+        let path = usage_path(&BillingEntity::organization("acme", None), "octocat");
+        assert_eq!(path, "/organizations/acme/settings/billing/premium_request/usage");
+        let path = usage_path(&BillingEntity::enterprise("acme-corp", None), "octocat");
+        assert_eq!(path, "/enterprises/acme-corp/settings/billing/premium_request/usage");
+    }
+
+    #[test]
+    fn reads_token_kind_and_scopes() {
+        assert_eq!(token_kind("github_pat_11ABC"), "fine-grained");
+        assert_eq!(token_kind("ghp_abc"), "classic");
+        assert_eq!(token_kind("gho_abc"), "other");
+        assert_eq!(parse_scopes("read:user, user ,"), ["read:user", "user"]);
+        assert!(parse_scopes("").is_empty());
+    }
+
+    #[test]
+    fn groups_reports_by_model() {
+        let query = UsageQuery::new(UsagePeriod::CurrentCycle, UsageGrouping::Model);
+        let result = build_result(7, &fixture_days(), None, 300, &query);
+
+        assert!(result.usage_history.is_none());
+        let models = result.usage_models.unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[1].name, "GPT-4.1");
+        assert_eq!(models[1].included_requests, 12);
+        assert_eq!(models[1].billed_requests, 3);
+    }
+}
@@ -0,0 +1,294 @@
+use serde_json::{Map, Value};
+
+use crate::store::{DEFAULT_TRAY_ICON_FORMAT, TRAY_ICON_FORMATS, UPDATE_CHANNELS};
+
+/// Settings schema version written by this build
+pub const CURRENT_SETTINGS_VERSION: u32 = 1;
+
+/// Valid theme preferences
+const THEMES: &[&str] = &["light", "dark", "system"];
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// Ordered migration chain: `MIGRATIONS[n]` upgrades a version `n` document to `n + 1`
+/// Never edit a shipped migration, append a new one and bump CURRENT_SETTINGS_VERSION
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+/// Schema version of a raw settings document (files written before versioning are 0)
+pub fn settings_version(value: &Value) -> u32 {
+    value
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// Upgrade a raw settings document to CURRENT_SETTINGS_VERSION in place
+/// Returns whether the document was changed and should be written back
+pub fn migrate_settings(value: &mut Value) -> Result<bool, String> {
+    let version = settings_version(value);
+    let settings = value
+        .as_object_mut()
+        .ok_or("Settings file is not a JSON object")?;
+
+    if version > CURRENT_SETTINGS_VERSION {
+        log::warn!(
+            "Settings schema v{} is newer than this app (v{}), loading as-is",
+            version,
+            CURRENT_SETTINGS_VERSION
+        );
+        return Ok(false);
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(settings);
+        settings.insert("schemaVersion".to_string(), Value::from(index as u32 + 1));
+        log::info!("Migrated settings to schema v{}", index + 1);
+    }
+
+    Ok(version < CURRENT_SETTINGS_VERSION)
+}
+
+/// Parse one notification threshold as written by any earlier version
+/// Accepts plain numbers, numeric strings and `{ "value" | "percent" | "threshold": n }` objects
+fn parse_threshold(value: &Value) -> Option<u32> {
+    let number = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().trim_end_matches('%').parse::<f64>().ok(),
+        Value::Object(obj) => ["value", "percent", "threshold"]
+            .iter()
+            .find_map(|key| obj.get(*key))
+            .and_then(parse_threshold)
+            .map(|t| t as f64),
+        _ => None,
+    }?;
+
+    if (1.0..=100.0).contains(&number) {
+        Some(number.round() as u32)
+    } else {
+        None
+    }
+}
+
+/// Replace a string field that is not one of `allowed` with `fallback`
+fn normalize_choice(
+    settings: &mut Map<String, Value>,
+    key: &str,
+    allowed: &[&str],
+    fallback: &str,
+) {
+    let valid = settings
+        .get(key)
+        .and_then(Value::as_str)
+        .map(|v| allowed.contains(&v))
+        .unwrap_or(false);
+    if !valid {
+        if let Some(old) = settings.get(key) {
+            log::warn!("Unknown {} {}, using {}", key, old, fallback);
+        }
+        settings.insert(key.to_string(), Value::from(fallback));
+    }
+}
+
+/// v0 -> v1: settings written before schema versioning
+/// - lifts the nested `notifications: { enabled, thresholds }` shape to top-level fields
+/// - normalizes notification thresholds to sorted, unique percentages
+/// - replaces unknown tray formats, update channels and themes with defaults
+/// - fills fields that older builds did not always write
+fn migrate_v0_to_v1(settings: &mut Map<String, Value>) {
+    if let Some(Value::Object(notifications)) = settings.remove("notifications") {
+        if !settings.contains_key("showNotifications") {
+            if let Some(enabled) = notifications.get("enabled").and_then(Value::as_bool) {
+                settings.insert("showNotifications".to_string(), Value::from(enabled));
+            }
+        }
+        if !settings.contains_key("notificationThresholds") {
+            if let Some(thresholds) = notifications.get("thresholds") {
+                settings.insert("notificationThresholds".to_string(), thresholds.clone());
+            }
+        }
+    }
+
+    if let Some(raw) = settings.remove("notificationThresholds") {
+        let mut thresholds: Vec<u32> = match &raw {
+            Value::Array(items) => items.iter().filter_map(parse_threshold).collect(),
+            other => parse_threshold(other).into_iter().collect(),
+        };
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        if thresholds.is_empty() {
+            log::warn!(
+                "No valid notification thresholds in {}, using defaults",
+                raw
+            );
+        } else {
+            settings.insert(
+                "notificationThresholds".to_string(),
+                Value::from(thresholds),
+            );
+        }
+    }
+
+    if settings.contains_key("trayIconFormat") {
+        normalize_choice(
+            settings,
+            "trayIconFormat",
+            TRAY_ICON_FORMATS,
+            DEFAULT_TRAY_ICON_FORMAT,
+        );
+    }
+    normalize_choice(settings, "updateChannel", UPDATE_CHANNELS, "stable");
+    if settings.contains_key("theme") {
+        normalize_choice(settings, "theme", THEMES, "system");
+    }
+
+    let required_defaults = [
+        ("usageLimit", Value::from(1200)),
+        ("lastUsage", Value::from(0)),
+        ("lastFetchTimestamp", Value::from(0)),
+        ("launchAtLogin", Value::from(false)),
+        ("showNotifications", Value::from(true)),
+        ("isAuthenticated", Value::from(false)),
+    ];
+    for (key, default) in required_defaults {
+        settings.entry(key).or_insert(default);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::AppSettings;
+    use serde_json::json;
+
+    fn load(mut raw: Value) -> AppSettings {
+        migrate_settings(&mut raw).expect("migration failed");
+        serde_json::from_value(raw).expect("migrated settings did not parse")
+    }
+
+    #[test]
+    fn migrates_unversioned_settings() {
+        let settings = load(json!({
+            "customerId": 42,
+            "usageLimit": 300,
+            "lastUsage": 120,
+            "lastFetchTimestamp": 1_760_000_000,
+            "lastUpdateCheckTimestamp": 1_760_000_100,
+            "launchAtLogin": true,
+            "showNotifications": false,
+            "notificationThresholds": [90, 75],
+            "updateChannel": "beta",
+            "isAuthenticated": true,
+            "refreshInterval": 300,
+            "predictionPeriod": 14,
+            "startMinimized": false,
+            "theme": "dark",
+            "trayIconFormat": "percentage",
+            "widgetEnabled": true,
+            "widgetPosition": { "x": 10, "y": 20 },
+            "widgetPinned": false,
+            "widgetVisible": true
+        }));
+
+        assert_eq!(settings.schema_version, CURRENT_SETTINGS_VERSION);
+        assert_eq!(settings.customer_id, Some(42));
+        assert_eq!(settings.last_usage, 120);
+        assert!(!settings.show_notifications);
+        assert_eq!(settings.notification_thresholds, vec![75, 90]);
+        assert_eq!(settings.update_channel, "beta");
+        assert_eq!(settings.tray_icon_format, "percentage");
+        assert_eq!(settings.widget_position.x, 10);
+    }
+
+    #[test]
+    fn migrates_early_settings_missing_later_fields() {
+        // Shape written before update channels, tray formats and the widget existed
+        let settings = load(json!({
+            "customerId": null,
+            "usageLimit": 300,
+            "lastUsage": 5,
+            "lastFetchTimestamp": 0,
+            "launchAtLogin": false,
+            "showNotifications": true,
+            "isAuthenticated": false
+        }));
+
+        assert_eq!(settings.update_channel, "stable");
+        assert_eq!(settings.tray_icon_format, DEFAULT_TRAY_ICON_FORMAT);
+        assert_eq!(settings.notification_thresholds, vec![75, 90, 100]);
+        assert!(!settings.widget_enabled);
+    }
+
+    #[test]
+    fn lifts_nested_notification_settings() {
+        let settings = load(json!({
+            "usageLimit": 300,
+            "notifications": { "enabled": false, "thresholds": [50, 100] },
+            "updateChannel": "stable"
+        }));
+
+        assert!(!settings.show_notifications);
+        assert_eq!(settings.notification_thresholds, vec![50, 100]);
+    }
+
+    #[test]
+    fn normalizes_threshold_shapes() {
+        let settings = load(json!({
+            "notificationThresholds": [
+                { "percent": 90 },
+                { "value": 75, "enabled": true },
+                "100%",
+                50.4,
+                150,
+                0,
+                "abc",
+                90
+            ]
+        }));
+
+        assert_eq!(settings.notification_thresholds, vec![50, 75, 90, 100]);
+    }
+
+    #[test]
+    fn falls_back_when_no_threshold_is_valid() {
+        let settings = load(json!({ "notificationThresholds": ["never", -5] }));
+
+        assert_eq!(settings.notification_thresholds, vec![75, 90, 100]);
+    }
+
+    #[test]
+    fn replaces_unknown_enum_values() {
+        let settings = load(json!({
+            "trayIconFormat": "iconOnly",
+            "updateChannel": "nightly",
+            "theme": "solarized"
+        }));
+
+        assert_eq!(settings.tray_icon_format, DEFAULT_TRAY_ICON_FORMAT);
+        assert_eq!(settings.update_channel, "stable");
+        assert_eq!(settings.theme, "system");
+    }
+
+    #[test]
+    fn leaves_current_settings_untouched() {
+        let mut raw = serde_json::to_value(AppSettings::default()).unwrap();
+        let original = raw.clone();
+
+        assert!(!migrate_settings(&mut raw).unwrap());
+        assert_eq!(raw, original);
+    }
+
+    #[test]
+    fn leaves_newer_settings_untouched() {
+        let mut raw = json!({ "schemaVersion": CURRENT_SETTINGS_VERSION + 1, "theme": "neon" });
+        let original = raw.clone();
+
+        assert!(!migrate_settings(&mut raw).unwrap());
+        assert_eq!(raw, original);
+    }
+
+    #[test]
+    fn rejects_non_object_settings() {
+        assert!(migrate_settings(&mut json!([1, 2, 3])).is_err());
+    }
+}
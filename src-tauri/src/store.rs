@@ -1,11 +1,27 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use crate::usage::UsageEntry;
+use crate::accounts::{self, AccountProfile, DEFAULT_ACCOUNT_ID};
+use crate::billing_entity::{self, BillingEntity, PERSONAL_ENTITY_KEY};
+use crate::calendar::{Clock, SystemClock};
+use crate::history_db::{HistoryDb, OpenError};
+use crate::notifications::NotificationState;
+use crate::persist;
+use crate::settings_migration::{self, CURRENT_SETTINGS_VERSION};
+use crate::usage::{CycleUsage, UsageEntry, UsageSnapshot};
 
 const STORE_FILENAME: &str = "settings.json";
 const HISTORY_FILENAME: &str = "usage_history.json";
+const NOTIFICATION_STATE_FILENAME: &str = "notification_state.json";
+const SNAPSHOTS_FILENAME: &str = "usage_snapshots.jsonl";
+const HISTORY_DB_FILENAME: &str = "history.db";
+/// Suffix given to legacy JSON files once their contents live in the database
+const IMPORTED_SUFFIX: &str = "imported";
+
+/// Minimum time between automatic snapshot compactions
+const SNAPSHOT_COMPACTION_INTERVAL_SECS: i64 = 6 * 60 * 60;
 
 /// Valid tray icon display formats
 pub const TRAY_ICON_FORMATS: &[&str] = &[
@@ -21,9 +37,18 @@ pub const TRAY_ICON_FORMATS: &[&str] = &[
 /// Default tray icon format - must be one of TRAY_ICON_FORMATS
 pub const DEFAULT_TRAY_ICON_FORMAT: &str = "currentTotal";
 
+/// Valid update channels
+pub const UPDATE_CHANNELS: &[&str] = &["stable", "beta"];
+
+/// Valid usage sources: scrape the billing page in a webview, or call the REST API with a token
+pub const USAGE_SOURCES: &[&str] = &["webview", "rest"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSettings {
+    /// Settings schema version (payloads without it are assumed current)
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     /// Customer ID from GitHub
     pub customer_id: Option<u64>,
     /// Usage limit for the current period
@@ -46,6 +71,9 @@ pub struct AppSettings {
     pub update_channel: String,
     /// Authenticated state
     pub is_authenticated: bool,
+    /// Set when GitHub sent the webview to its sign-in page; polling waits for a new sign-in
+    #[serde(default)]
+    pub session_expired: bool,
     /// Refresh interval in seconds
     #[serde(default = "default_refresh_interval")]
     pub refresh_interval: u32,
@@ -73,6 +101,47 @@ pub struct AppSettings {
     /// Widget visible
     #[serde(default = "default_widget_visible")]
     pub widget_visible: bool,
+    /// Days to keep intra-day usage snapshots (0 keeps them forever)
+    #[serde(default = "default_snapshot_retention_days")]
+    pub snapshot_retention_days: u32,
+    /// Snapshots older than this many days are compacted
+    #[serde(default = "default_snapshot_compaction_after_days")]
+    pub snapshot_compaction_after_days: u32,
+    /// Compacted snapshots keep the last sample per bucket of this many minutes
+    #[serde(default = "default_snapshot_compaction_bucket_minutes")]
+    pub snapshot_compaction_bucket_minutes: u32,
+    /// Day of the month the quota resets on (1-31, clamped to short months)
+    #[serde(default = "default_billing_cycle_anchor_day")]
+    pub billing_cycle_anchor_day: u32,
+    /// Time zone the quota resets in ("UTC", "local" or an offset like "+08:00")
+    #[serde(default = "default_billing_time_zone")]
+    pub billing_time_zone: String,
+    /// Holidays ("YYYY-MM-DD") predicted like weekends
+    #[serde(default)]
+    pub holidays: Vec<String>,
+    /// Where usage comes from - must be one of USAGE_SOURCES
+    #[serde(default = "default_usage_source")]
+    pub usage_source: String,
+    /// Monthly premium request allowance, which the REST API does not report
+    #[serde(default = "default_premium_request_limit")]
+    pub premium_request_limit: u32,
+    /// Billing entities found for the account, personal first
+    #[serde(default = "default_billing_entities")]
+    pub billing_entities: Vec<BillingEntity>,
+    /// Keys of the entities whose usage is tracked; the personal account drives the tray
+    /// and is always fetched
+    #[serde(default = "default_selected_billing_entities")]
+    pub selected_billing_entities: Vec<String>,
+    /// GitHub accounts tracked by the app, the default account first
+    /// Only read from the default account's settings
+    #[serde(default = "default_accounts")]
+    pub accounts: Vec<AccountProfile>,
+    /// Id of the account shown in the tray
+    #[serde(default = "default_displayed_account")]
+    pub displayed_account: String,
+    /// Show the usage summed over all signed-in accounts in the tray
+    #[serde(default)]
+    pub aggregate_tray_text: bool,
 }
 
 /// Widget position on screen
@@ -89,6 +158,10 @@ impl Default for WidgetPosition {
     }
 }
 
+fn default_schema_version() -> u32 {
+    CURRENT_SETTINGS_VERSION
+}
+
 fn default_thresholds() -> Vec<u32> {
     vec![75, 90, 100]
 }
@@ -129,9 +202,70 @@ fn default_widget_position() -> WidgetPosition {
     WidgetPosition::default()
 }
 
+fn default_snapshot_retention_days() -> u32 {
+    365
+}
+
+fn default_snapshot_compaction_after_days() -> u32 {
+    7
+}
+
+fn default_snapshot_compaction_bucket_minutes() -> u32 {
+    60
+}
+
+fn default_billing_cycle_anchor_day() -> u32 {
+    1
+}
+
+fn default_billing_time_zone() -> String {
+    "UTC".to_string()
+}
+
+fn default_usage_source() -> String {
+    "webview".to_string()
+}
+
+fn default_premium_request_limit() -> u32 {
+    300
+}
+
+fn default_billing_entities() -> Vec<BillingEntity> {
+    vec![BillingEntity::personal()]
+}
+
+fn default_selected_billing_entities() -> Vec<String> {
+    vec![PERSONAL_ENTITY_KEY.to_string()]
+}
+
+fn default_accounts() -> Vec<AccountProfile> {
+    vec![AccountProfile::default_account()]
+}
+
+fn default_displayed_account() -> String {
+    DEFAULT_ACCOUNT_ID.to_string()
+}
+
+fn default_entity_key() -> String {
+    PERSONAL_ENTITY_KEY.to_string()
+}
+
+impl AppSettings {
+    /// Account shown in the tray; the default account when `displayed_account` names none
+    /// of `accounts`, e.g. after settings.json was edited or restored from an older copy
+    pub fn displayed_account_id(&self) -> &str {
+        if self.accounts.iter().any(|a| a.id == self.displayed_account) {
+            &self.displayed_account
+        } else {
+            DEFAULT_ACCOUNT_ID
+        }
+    }
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            schema_version: default_schema_version(),
             customer_id: None,
             usage_limit: 1200, // Default Copilot limit
             last_usage: 0,
@@ -142,6 +276,7 @@ impl Default for AppSettings {
             notification_thresholds: default_thresholds(),
             update_channel: "stable".to_string(),
             is_authenticated: false,
+            session_expired: false,
             refresh_interval: default_refresh_interval(),
             prediction_period: default_prediction_period(),
             start_minimized: default_start_minimized(),
@@ -151,12 +286,28 @@ impl Default for AppSettings {
             widget_position: default_widget_position(),
             widget_pinned: default_widget_pinned(),
             widget_visible: default_widget_visible(),
+            snapshot_retention_days: default_snapshot_retention_days(),
+            snapshot_compaction_after_days: default_snapshot_compaction_after_days(),
+            snapshot_compaction_bucket_minutes: default_snapshot_compaction_bucket_minutes(),
+            billing_cycle_anchor_day: default_billing_cycle_anchor_day(),
+            billing_time_zone: default_billing_time_zone(),
+            holidays: Vec::new(),
+            usage_source: default_usage_source(),
+            premium_request_limit: default_premium_request_limit(),
+            billing_entities: default_billing_entities(),
+            selected_billing_entities: default_selected_billing_entities(),
+            accounts: default_accounts(),
+            displayed_account: default_displayed_account(),
+            aggregate_tray_text: false,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageCache {
+    /// Key of the billing entity the usage belongs to
+    #[serde(default = "default_entity_key")]
+    pub entity: String,
     pub customer_id: u64,
     pub net_quantity: u64,
     pub discount_quantity: u64,
@@ -167,11 +318,14 @@ pub struct UsageCache {
 }
 
 pub struct StoreManager {
+    app_dir: PathBuf,
     settings_path: PathBuf,
-    history_path: PathBuf,
     settings: Mutex<AppSettings>,
-    usage_cache: Mutex<Option<UsageCache>>,
-    usage_history: Mutex<Vec<UsageEntry>>,
+    /// Latest usage per billing entity key
+    usage_caches: Mutex<HashMap<String, UsageCache>>,
+    history_db: HistoryDb,
+    /// Timestamp of the last snapshot compaction (0 = not yet compacted this session)
+    last_snapshot_compaction: Mutex<i64>,
 }
 
 impl StoreManager {
@@ -184,60 +338,131 @@ impl StoreManager {
         }
 
         let settings_path = app_dir.join(STORE_FILENAME);
-        let history_path = app_dir.join(HISTORY_FILENAME);
 
         // Load existing settings or create defaults
-        let settings = if settings_path.exists() {
-            Self::load_settings_from_disk(&settings_path)?
+        let settings = if settings_path.exists() || persist::backup_path(&settings_path).exists() {
+            Self::load_settings_from_disk(&settings_path)
         } else {
             AppSettings::default()
         };
 
-        // Load existing history or create empty
-        let history = if history_path.exists() {
-            Self::load_history_from_disk(&history_path)?
-        } else {
-            Vec::new()
+        // Open the history database and pull in any pre-database JSON files
+        let history_db_path = app_dir.join(HISTORY_DB_FILENAME);
+        let history_db = match HistoryDb::open(&history_db_path) {
+            Ok(db) => db,
+            Err(OpenError::Corrupt(e)) => {
+                // Keep the unreadable database for inspection and start a fresh one
+                log::error!("{}, moving it aside", e);
+                HistoryDb::move_aside(&history_db_path)?;
+                HistoryDb::open(&history_db_path).map_err(|e| e.to_string())?
+            }
+            // A newer schema or an I/O error is not corruption; refuse to start rather than
+            // discard history that is still valid
+            Err(e) => return Err(e.to_string()),
         };
+        Self::import_legacy_files(&history_db, &app_dir);
 
-        Ok(Self {
+        let manager = Self {
+            app_dir,
             settings_path,
-            history_path,
             settings: Mutex::new(settings),
-            usage_cache: Mutex::new(None),
-            usage_history: Mutex::new(history),
-        })
+            usage_caches: Mutex::new(HashMap::new()),
+            history_db,
+            last_snapshot_compaction: Mutex::new(0),
+        };
+
+        // Apply retention/compaction once at startup
+        if let Err(e) = manager.compact_usage_snapshots(SystemClock.now().timestamp()) {
+            log::error!("Failed to compact usage snapshots: {}", e);
+        }
+
+        Ok(manager)
     }
 
-    /// Load settings from disk
-    fn load_settings_from_disk(path: &PathBuf) -> Result<AppSettings, String> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read settings file: {}", e))?;
+    /// One-time import of the JSON files used before the history database
+    /// Each file is renamed once imported; a file that fails to import is left in place
+    /// so the next start retries it
+    fn import_legacy_files(db: &HistoryDb, app_dir: &Path) {
+        let history_path = app_dir.join(HISTORY_FILENAME);
+        if history_path.exists() {
+            let result = Self::load_history_from_disk(&history_path)
+                .and_then(|history| db.upsert_daily_entries(&history).map(|_| history.len()));
+            Self::finish_legacy_import(&history_path, "usage history", result);
+        }
 
-        let settings: AppSettings = serde_json::from_str(&content)
+        let snapshots_path = app_dir.join(SNAPSHOTS_FILENAME);
+        if snapshots_path.exists() {
+            let result = Self::load_snapshots_from_disk(&snapshots_path)
+                .and_then(|snapshots| db.insert_snapshots(&snapshots).map(|_| snapshots.len()));
+            Self::finish_legacy_import(&snapshots_path, "usage snapshots", result);
+        }
+
+        let notification_state_path = app_dir.join(NOTIFICATION_STATE_FILENAME);
+        if notification_state_path.exists() {
+            let result = Self::load_notification_state_from_disk(&notification_state_path)
+                .and_then(|state| {
+                    db.save_notification_state(&state)
+                        .map(|_| state.fired_thresholds.len())
+                });
+            Self::finish_legacy_import(&notification_state_path, "notification state", result);
+        }
+    }
+
+    fn finish_legacy_import(path: &Path, what: &str, result: Result<usize, String>) {
+        match result {
+            Ok(count) => {
+                log::info!("Imported {} {} entries into history database", count, what);
+                let mut imported = path.as_os_str().to_owned();
+                imported.push(format!(".{}", IMPORTED_SUFFIX));
+                if let Err(e) = std::fs::rename(path, &imported) {
+                    log::warn!("Failed to rename imported {} file: {}", what, e);
+                }
+            }
+            Err(e) => log::error!("Failed to import {} into history database: {}", what, e),
+        }
+    }
+
+    /// Parse settings file contents, migrating older schema versions
+    /// Returns the settings and whether a migration changed them
+    fn parse_settings(content: &str) -> Result<(AppSettings, bool), String> {
+        let mut raw: serde_json::Value = serde_json::from_str(content)
             .map_err(|e| format!("Failed to parse settings file: {}", e))?;
+        let migrated = settings_migration::migrate_settings(&mut raw)?;
 
-        Ok(settings)
+        let settings = serde_json::from_value(raw)
+            .map_err(|e| format!("Failed to parse settings file: {}", e))?;
+
+        Ok((settings, migrated))
+    }
+
+    /// Load settings from disk, recovering from the backup copy if the file is corrupt
+    /// Migrated settings are written back so the file is only upgraded once
+    fn load_settings_from_disk(path: &Path) -> AppSettings {
+        match persist::load_with_backup(path, Self::parse_settings) {
+            Some((settings, migrated)) => {
+                if migrated {
+                    if let Err(e) = Self::save_settings_to_disk(path, &settings) {
+                        log::error!("Failed to save migrated settings: {}", e);
+                    }
+                }
+                settings
+            }
+            None => {
+                log::warn!("No usable settings file, starting with defaults");
+                AppSettings::default()
+            }
+        }
     }
 
-    /// Save settings to disk
-    fn save_settings_to_disk(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
+    /// Save settings to disk atomically (temp file + fsync + rename)
+    fn save_settings_to_disk(path: &Path, settings: &AppSettings) -> Result<(), String> {
         let content = serde_json::to_string_pretty(settings)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-        std::fs::write(path, content)
-            .map_err(|e| format!("Failed to write settings file: {}", e))?;
-
-        // Ensure data is flushed to disk (important for shutdown scenarios)
-        let file = std::fs::File::open(path)
-            .map_err(|e| format!("Failed to open settings file for sync: {}", e))?;
-        file.sync_all()
-            .map_err(|e| format!("Failed to sync settings file: {}", e))?;
-
-        Ok(())
+        persist::write_atomic(path, content.as_bytes())
     }
 
-    /// Load history from disk
+    /// Load legacy history JSON
     fn load_history_from_disk(path: &PathBuf) -> Result<Vec<UsageEntry>, String> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| format!("Failed to read history file: {}", e))?;
@@ -248,15 +473,38 @@ impl StoreManager {
         Ok(history)
     }
 
-    /// Save history to disk
-    fn save_history_to_disk(path: &PathBuf, history: &Vec<UsageEntry>) -> Result<(), String> {
-        let content = serde_json::to_string_pretty(history)
-            .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    /// Load legacy notification state JSON
+    fn load_notification_state_from_disk(path: &Path) -> Result<NotificationState, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read notification state file: {}", e))?;
 
-        std::fs::write(path, content)
-            .map_err(|e| format!("Failed to write history file: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse notification state file: {}", e))
+    }
 
-        Ok(())
+    /// Load legacy snapshots JSON Lines, skipping lines left truncated by a crash
+    fn load_snapshots_from_disk(path: &Path) -> Result<Vec<UsageSnapshot>, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read snapshots file: {}", e))?;
+
+        let mut snapshots = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<UsageSnapshot>(line) {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(e) => log::warn!("Skipping invalid snapshot on line {}: {}", index + 1, e),
+            }
+        }
+        snapshots.sort_by_key(|s| s.timestamp);
+
+        Ok(snapshots)
+    }
+
+    /// App data directory backing this store
+    pub fn data_dir(&self) -> PathBuf {
+        self.app_dir.clone()
     }
 
     /// Get a copy of current settings
@@ -283,6 +531,7 @@ impl StoreManager {
         self.update_settings(|s| {
             s.customer_id = Some(id);
             s.is_authenticated = true;
+            s.session_expired = false;
         })
     }
 
@@ -291,12 +540,12 @@ impl StoreManager {
         self.settings.lock().unwrap().customer_id
     }
 
-    /// Set usage data
-    pub fn set_usage(&self, used: u32, limit: u32) -> Result<(), String> {
+    /// Set usage data fetched at `now`
+    pub fn set_usage(&self, used: u32, limit: u32, now: i64) -> Result<(), String> {
         self.update_settings(|s| {
             s.last_usage = used;
             s.usage_limit = limit;
-            s.last_fetch_timestamp = chrono::Utc::now().timestamp();
+            s.last_fetch_timestamp = now;
         })
     }
 
@@ -347,16 +596,128 @@ impl StoreManager {
         self.settings.lock().unwrap().show_notifications
     }
 
+    /// Get the update channel
+    pub fn get_update_channel(&self) -> String {
+        self.settings.lock().unwrap().update_channel.clone()
+    }
+
+    /// Set the update channel with validation
+    pub fn set_update_channel(&self, channel: String) -> Result<(), String> {
+        if !UPDATE_CHANNELS.contains(&channel.as_str()) {
+            return Err(format!("Invalid update channel: {}", channel));
+        }
+
+        self.update_settings(|s| {
+            s.update_channel = channel;
+        })
+    }
+
+    /// Set the billing cycle anchor day and reset time zone with validation
+    pub fn set_billing_cycle(&self, anchor_day: u32, time_zone: String) -> Result<(), String> {
+        if !(1..=31).contains(&anchor_day) {
+            return Err(format!("Invalid billing cycle anchor day: {}", anchor_day));
+        }
+        if !crate::billing::is_valid_time_zone(&time_zone) {
+            return Err(format!("Invalid billing time zone: {}", time_zone));
+        }
+
+        self.update_settings(|s| {
+            s.billing_cycle_anchor_day = anchor_day;
+            s.billing_time_zone = time_zone;
+        })
+    }
+
+    /// Select the usage source
+    pub fn set_usage_source(&self, source: String) -> Result<(), String> {
+        if !USAGE_SOURCES.contains(&source.as_str()) {
+            return Err(format!("Invalid usage source: {}", source));
+        }
+        self.update_settings(|s| s.usage_source = source)
+    }
+
+    /// Add an account profile named `name`
+    pub fn add_account(&self, name: String) -> Result<AccountProfile, String> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Account name is empty".to_string());
+        }
+        let mut profile = None;
+        self.update_settings(|s| {
+            let added = AccountProfile {
+                id: accounts::new_account_id(&name, &s.accounts),
+                name,
+            };
+            s.accounts.push(added.clone());
+            profile = Some(added);
+        })?;
+        profile.ok_or_else(|| "Failed to add account".to_string())
+    }
+
+    pub fn rename_account(&self, id: &str, name: String) -> Result<(), String> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Account name is empty".to_string());
+        }
+        if !self.get_settings().accounts.iter().any(|a| a.id == id) {
+            return Err(format!("Unknown account: {}", id));
+        }
+        self.update_settings(|s| {
+            if let Some(account) = s.accounts.iter_mut().find(|a| a.id == id) {
+                account.name = name;
+            }
+        })
+    }
+
+    /// Remove an account profile; the tray falls back to the default account if it showed it
+    /// The account's data directory is deleted by `AccountStores::delete`
+    pub fn remove_account(&self, id: &str) -> Result<(), String> {
+        if id == DEFAULT_ACCOUNT_ID {
+            return Err("The default account cannot be removed".to_string());
+        }
+        if !self.get_settings().accounts.iter().any(|a| a.id == id) {
+            return Err(format!("Unknown account: {}", id));
+        }
+        self.update_settings(|s| {
+            s.accounts.retain(|a| a.id != id);
+            if s.displayed_account == id {
+                s.displayed_account = default_displayed_account();
+            }
+        })
+    }
+
+    /// Choose the account shown in the tray
+    pub fn set_displayed_account(&self, id: &str) -> Result<(), String> {
+        if !self.get_settings().accounts.iter().any(|a| a.id == id) {
+            return Err(format!("Unknown account: {}", id));
+        }
+        self.update_settings(|s| s.displayed_account = id.to_string())
+    }
+
     /// Check if authenticated
     pub fn is_authenticated(&self) -> bool {
         self.settings.lock().unwrap().is_authenticated
     }
 
+    /// Whether the GitHub session expired since the last sign-in
+    pub fn is_session_expired(&self) -> bool {
+        self.settings.lock().unwrap().session_expired
+    }
+
+    /// Record that the GitHub session expired; returns whether it was not known yet
+    pub fn mark_session_expired(&self) -> Result<bool, String> {
+        if self.is_session_expired() {
+            return Ok(false);
+        }
+        self.update_settings(|s| s.session_expired = true)?;
+        Ok(true)
+    }
+
     /// Clear authentication (logout)
     pub fn clear_auth(&self) -> Result<(), String> {
         self.update_settings(|s| {
             s.customer_id = None;
             s.is_authenticated = false;
+            s.session_expired = false;
         })
     }
 
@@ -367,6 +728,7 @@ impl StoreManager {
         let customer_id = settings.customer_id.ok_or("No customer ID available")?;
 
         Ok(UsageCache {
+            entity: default_entity_key(),
             customer_id,
             net_quantity: settings.last_usage as u64,
             discount_quantity: 0,
@@ -377,33 +739,156 @@ impl StoreManager {
         })
     }
 
+    /// Store `cache` as the latest usage of its entity
     pub fn set_usage_cache(&self, cache: UsageCache) {
-        let mut guard = self.usage_cache.lock().unwrap();
-        *guard = Some(cache);
+        let mut guard = self.usage_caches.lock().unwrap();
+        guard.insert(cache.entity.clone(), cache);
     }
 
+    /// Latest usage of the personal account
     pub fn get_usage_cache(&self) -> Option<UsageCache> {
-        self.usage_cache.lock().unwrap().clone()
+        self.get_entity_usage_cache(PERSONAL_ENTITY_KEY)
     }
 
-    pub fn set_usage_history(&self, history: Vec<UsageEntry>) {
-        let mut guard = self.usage_history.lock().unwrap();
-        *guard = history.clone();
-        drop(guard); // Release lock before disk I/O
+    pub fn get_entity_usage_cache(&self, key: &str) -> Option<UsageCache> {
+        self.usage_caches.lock().unwrap().get(key).cloned()
+    }
 
-        // Persist to disk
-        if let Err(e) = Self::save_history_to_disk(&self.history_path, &history) {
-            log::error!("Failed to save usage history to disk: {}", e);
+    /// Latest usage of each selected entity fetched so far, in the order of `billing_entities`
+    pub fn get_usage_caches(&self) -> Vec<UsageCache> {
+        let caches = self.usage_caches.lock().unwrap();
+        self.selected_billing_entities()
+            .iter()
+            .filter_map(|entity| caches.get(&entity.key()).cloned())
+            .collect()
+    }
+
+    /// Record the entities found for the account (see `billing_entity::merge_entities`)
+    pub fn set_billing_entities(&self, discovered: Vec<BillingEntity>) -> Result<(), String> {
+        self.update_settings(|s| {
+            s.billing_entities =
+                billing_entity::merge_entities(&s.billing_entities, discovered, &s.selected_billing_entities);
+        })
+    }
+
+    /// Choose which known entities to track; the personal account is always kept
+    pub fn set_selected_billing_entities(&self, keys: Vec<String>) -> Result<(), String> {
+        let known = self.get_settings().billing_entities;
+        if let Some(unknown) = keys.iter().find(|key| !known.iter().any(|e| &e.key() == *key)) {
+            return Err(format!("Unknown billing entity: {}", unknown));
+        }
+
+        let mut selected = default_selected_billing_entities();
+        for key in keys {
+            if !selected.contains(&key) {
+                selected.push(key);
+            }
+        }
+        self.update_settings(|s| s.selected_billing_entities = selected)
+    }
+
+    /// Known entities whose usage is tracked
+    pub fn selected_billing_entities(&self) -> Vec<BillingEntity> {
+        let settings = self.settings.lock().unwrap();
+        settings
+            .billing_entities
+            .iter()
+            .filter(|entity| settings.selected_billing_entities.contains(&entity.key()))
+            .cloned()
+            .collect()
+    }
+
+    /// Merge freshly extracted daily rows into the stored history
+    /// Days present in `history` replace stored ones; older days that GitHub no longer
+    /// returns are kept
+    pub fn set_usage_history(&self, history: Vec<UsageEntry>) {
+        if let Err(e) = self.history_db.upsert_daily_entries(&history) {
+            log::error!("Failed to save usage history: {}", e);
         } else {
-            log::info!(
-                "Successfully saved {} history entries to disk",
-                history.len()
-            );
+            log::info!("Successfully saved {} history entries", history.len());
         }
     }
 
     pub fn get_usage_history(&self) -> Vec<UsageEntry> {
-        self.usage_history.lock().unwrap().clone()
+        self.history_db.load_daily_entries().unwrap_or_else(|e| {
+            log::error!("Failed to load usage history: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Store the totals of a billing cycle, replacing earlier ones for the same cycle
+    pub fn set_cycle_usage(&self, usage: &CycleUsage) {
+        if let Err(e) = self.history_db.upsert_cycle_usage(usage) {
+            log::error!("Failed to save cycle usage: {}", e);
+        }
+    }
+
+    pub fn get_cycle_usage(&self, cycle_key: &str) -> Option<CycleUsage> {
+        self.history_db.load_cycle_usage(cycle_key).unwrap_or_else(|e| {
+            log::error!("Failed to load cycle usage: {}", e);
+            None
+        })
+    }
+
+    /// Record a usage snapshot (compacted periodically, timed by the snapshot's timestamp)
+    pub fn append_usage_snapshot(&self, snapshot: UsageSnapshot) {
+        let now = snapshot.timestamp;
+        if let Err(e) = self.history_db.insert_snapshots(&[snapshot]) {
+            log::error!("Failed to save usage snapshot: {}", e);
+        }
+
+        let last_compaction = *self.last_snapshot_compaction.lock().unwrap();
+        if now - last_compaction >= SNAPSHOT_COMPACTION_INTERVAL_SECS {
+            if let Err(e) = self.compact_usage_snapshots(now) {
+                log::error!("Failed to compact usage snapshots: {}", e);
+            }
+        }
+    }
+
+    /// Get usage snapshots (oldest first), optionally only those at or after `since`
+    pub fn get_usage_snapshots(&self, since: Option<i64>) -> Vec<UsageSnapshot> {
+        self.history_db.load_snapshots(since).unwrap_or_else(|e| {
+            log::error!("Failed to load usage snapshots: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Apply the configured retention and compaction policies to the snapshots
+    /// Snapshots past the retention window are dropped; older than the compaction age keep
+    /// only the last sample per bucket
+    pub fn compact_usage_snapshots(&self, now: i64) -> Result<(), String> {
+        let settings = self.get_settings();
+        *self.last_snapshot_compaction.lock().unwrap() = now;
+
+        let retention_cutoff = if settings.snapshot_retention_days > 0 {
+            Some(now - settings.snapshot_retention_days as i64 * 86_400)
+        } else {
+            None
+        };
+        let compaction_cutoff = now - settings.snapshot_compaction_after_days as i64 * 86_400;
+        let bucket_secs = settings.snapshot_compaction_bucket_minutes.max(1) as i64 * 60;
+
+        let removed =
+            self.history_db
+                .compact_snapshots(retention_cutoff, compaction_cutoff, bucket_secs)?;
+        if removed > 0 {
+            log::info!("Compacted usage snapshots: removed {} entries", removed);
+        }
+
+        Ok(())
+    }
+
+    /// Get thresholds already notified in the current billing cycle
+    pub fn get_notification_state(&self) -> NotificationState {
+        self.history_db.load_notification_state().unwrap_or_else(|e| {
+            log::warn!("{}, starting with empty notification state", e);
+            NotificationState::default()
+        })
+    }
+
+    /// Set notification state and persist it
+    pub fn set_notification_state(&self, state: NotificationState) -> Result<(), String> {
+        self.history_db.save_notification_state(&state)
     }
 
     pub fn reset_settings(&self) -> Result<AppSettings, String> {
@@ -412,23 +897,11 @@ impl StoreManager {
             *s = defaults.clone();
         })?;
 
-        // Clear usage cache
-        {
-            let mut cache = self.usage_cache.lock().unwrap();
-            *cache = None;
-        }
+        // Clear usage caches
+        self.usage_caches.lock().unwrap().clear();
 
-        // Clear usage history
-        {
-            let mut history = self.usage_history.lock().unwrap();
-            history.clear();
-        }
-
-        // Delete history file from disk
-        if self.history_path.exists() {
-            std::fs::remove_file(&self.history_path)
-                .map_err(|e| format!("Failed to delete history file: {}", e))?;
-        }
+        // Clear usage history, snapshots and fired notification thresholds
+        self.history_db.clear()?;
 
         Ok(defaults)
     }
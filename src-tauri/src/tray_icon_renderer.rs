@@ -4,6 +4,9 @@ use tiny_skia::Pixmap;
 /// Can be overridden for Windows high DPI scenarios
 const DEFAULT_SCALE_FACTOR: u32 = 2;
 
+/// Opacity of the text of a stale icon, so old numbers read as faded
+const STALE_TEXT_OPACITY: f32 = 0.5;
+
 #[derive(Clone, Debug)]
 pub struct TrayImage {
     rgba: Vec<u8>,
@@ -91,10 +94,37 @@ impl TrayIconRenderer {
     /// # Returns
     /// A TrayImage containing the rendered text as RGBA pixel data
     pub fn render_text_only(&self, text: &str, size_px: u32, color: (u8, u8, u8)) -> TrayImage {
+        self.render_text(text, size_px, color, false)
+    }
+
+    /// Renders text as a tray icon image, marked as stale when `stale` is set
+    ///
+    /// A stale icon has faded text and a dot in its top-right corner, so data that has not
+    /// been refreshed for a while does not look live
+    ///
+    /// # Arguments
+    /// * `text` - The text string to render
+    /// * `size_px` - The desired icon size in pixels
+    /// * `color` - RGB color tuple (r, g, b) for the text and marker (0-255)
+    /// * `stale` - Whether to draw the stale marker
+    ///
+    /// # Returns
+    /// A TrayImage containing the rendered text as RGBA pixel data
+    pub fn render_text(
+        &self,
+        text: &str,
+        size_px: u32,
+        color: (u8, u8, u8),
+        stale: bool,
+    ) -> TrayImage {
         // Use configured scale factor (2x for Retina, varies for Windows)
         let scaled_size = size_px * self.scale_factor;
         let scaled_font_px = self.font_px * self.scale_factor as f32;
         let padding_x = 4 * self.scale_factor as i32; // Scaled padding
+        // The stale marker gets its own space right of the text
+        let marker_radius = 2 * self.scale_factor as i32;
+        let marker_space = if stale { 3 * marker_radius } else { 0 };
+        let text_opacity = if stale { STALE_TEXT_OPACITY } else { 1.0 };
 
         // First pass: Calculate total width at scaled size
         let mut total_width = 0;
@@ -106,7 +136,7 @@ impl TrayIconRenderer {
             total_width += metrics.advance_width.round() as i32;
         }
 
-        let content_width = total_width + (padding_x * 2);
+        let content_width = total_width + (padding_x * 2) + marker_space;
         let width = (content_width as u32).max(scaled_size);
         let height = scaled_size;
 
@@ -124,8 +154,8 @@ impl TrayIconRenderer {
         // Better vertical centering calculation
         let baseline = (height as f32 * 0.75) as i32; // Standard typographic baseline
 
-        // Center text horizontally
-        let mut pen_x = ((width as i32 - total_width) / 2).max(0);
+        // Center text horizontally, left of the marker space
+        let mut pen_x = ((width as i32 - marker_space - total_width) / 2).max(0);
 
         for (metrics, alpha) in char_metrics {
             let glyph_w = metrics.width as i32;
@@ -151,7 +181,7 @@ impl TrayIconRenderer {
                         rgba[dst_index] = color.0; // R
                         rgba[dst_index + 1] = color.1; // G
                         rgba[dst_index + 2] = color.2; // B
-                        rgba[dst_index + 3] = a; // Alpha
+                        rgba[dst_index + 3] = (a as f32 * text_opacity) as u8; // Alpha
                     }
                 }
             }
@@ -159,6 +189,28 @@ impl TrayIconRenderer {
             pen_x += metrics.advance_width.round() as i32;
         }
 
+        if stale {
+            // Filled dot in the top-right corner
+            let center_x = width as i32 - padding_x / 2 - marker_radius;
+            let center_y = marker_radius + self.scale_factor as i32;
+            for y in (center_y - marker_radius)..=(center_y + marker_radius) {
+                for x in (center_x - marker_radius)..=(center_x + marker_radius) {
+                    let (dx, dy) = (x - center_x, y - center_y);
+                    if dx * dx + dy * dy > marker_radius * marker_radius {
+                        continue;
+                    }
+                    if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                        continue;
+                    }
+                    let dst_index = ((y as u32 * width + x as u32) * 4) as usize;
+                    rgba[dst_index] = color.0;
+                    rgba[dst_index + 1] = color.1;
+                    rgba[dst_index + 2] = color.2;
+                    rgba[dst_index + 3] = 255;
+                }
+            }
+        }
+
         TrayImage::new(pixmap.data().to_vec(), width, height)
     }
 }
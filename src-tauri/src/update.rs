@@ -0,0 +1,669 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// Minimum bytes between download progress callbacks
+const PROGRESS_REPORT_BYTES: u64 = 256 * 1024;
+
+/// Combined checksum listings recognised in a release (compared case-insensitively)
+const CHECKSUM_LISTING_NAMES: &[&str] = &[
+    "sha256sums",
+    "sha256sums.txt",
+    "checksums.txt",
+    "checksums.sha256",
+];
+
+/// GitHub releases listing used by the update checker (newest first)
+pub const GITHUB_RELEASES_API_URL: &str =
+    "https://api.github.com/repos/bizzkoot/copilot-tracker/releases?per_page=30";
+
+/// Parse a release tag such as "v2.4.0" or "2.5.0-beta.1" into a semver version
+pub fn parse_release_version(tag: &str) -> Option<semver::Version> {
+    semver::Version::parse(tag.trim().trim_start_matches('v')).ok()
+}
+
+fn release_version(release: &Value) -> Option<semver::Version> {
+    release
+        .get("tag_name")
+        .and_then(|v| v.as_str())
+        .and_then(parse_release_version)
+}
+
+/// Check whether a release may be offered on the given update channel
+/// Drafts are never eligible; the stable channel also skips prereleases and semver pre-release tags
+pub fn is_release_eligible(release: &Value, channel: &str) -> bool {
+    if release.get("draft").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return false;
+    }
+
+    let version = match release_version(release) {
+        Some(version) => version,
+        None => return false,
+    };
+
+    match channel {
+        "beta" => true,
+        // Unknown channels fall back to stable
+        _ => {
+            let prerelease = release
+                .get("prerelease")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            !prerelease && version.pre.is_empty()
+        }
+    }
+}
+
+/// Pick the newest release eligible for the channel from a GitHub releases listing
+pub fn select_release_for_channel(releases: &[Value], channel: &str) -> Option<Value> {
+    releases
+        .iter()
+        .filter(|release| is_release_eligible(release, channel))
+        .filter_map(|release| release_version(release).map(|version| (version, release)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, release)| release.clone())
+}
+
+/// A downloadable installer attached to a release, classified for the current platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub download_url: String,
+    pub size: u64,
+    /// Target operating system ("linux", "windows", "macos")
+    pub os: String,
+    /// Target architecture ("x86_64", "aarch64", "x86", "universal" or "unknown")
+    pub arch: String,
+    /// Package type ("appimage", "deb", "rpm", "msi", "exe", "dmg", "app.tar.gz")
+    pub package: String,
+    /// SHA-256 checksum file published for this asset, if any
+    pub checksum_url: Option<String>,
+}
+
+/// Platform the update is selected for
+#[derive(Debug, Clone)]
+pub struct UpdateTarget {
+    pub os: String,
+    pub arch: String,
+    /// Preferred native Linux package type ("deb" or "rpm") when it can be detected
+    pub linux_package: Option<String>,
+}
+
+impl UpdateTarget {
+    /// Describe the platform this binary is running on
+    pub fn current() -> Self {
+        let linux_package = if cfg!(target_os = "linux") {
+            if std::path::Path::new("/etc/debian_version").exists() {
+                Some("deb".to_string())
+            } else if std::path::Path::new("/etc/redhat-release").exists()
+                || std::path::Path::new("/etc/fedora-release").exists()
+                || std::path::Path::new("/etc/SuSE-release").exists()
+            {
+                Some("rpm".to_string())
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        Self {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            linux_package,
+        }
+    }
+}
+
+/// Classify an asset file name into (os, package), ignoring signatures and metadata files
+fn classify_asset_package(name: &str) -> Option<(&'static str, &'static str)> {
+    let lower = name.to_ascii_lowercase();
+    if lower.ends_with(".appimage") {
+        Some(("linux", "appimage"))
+    } else if lower.ends_with(".deb") {
+        Some(("linux", "deb"))
+    } else if lower.ends_with(".rpm") {
+        Some(("linux", "rpm"))
+    } else if lower.ends_with(".msi") {
+        Some(("windows", "msi"))
+    } else if lower.ends_with(".exe") {
+        Some(("windows", "exe"))
+    } else if lower.ends_with(".dmg") {
+        Some(("macos", "dmg"))
+    } else if lower.ends_with(".app.tar.gz") {
+        Some(("macos", "app.tar.gz"))
+    } else {
+        None
+    }
+}
+
+/// Detect the architecture encoded in an asset file name
+fn detect_asset_arch(name: &str) -> &'static str {
+    let lower = name.to_ascii_lowercase();
+    if lower.contains("universal") {
+        "universal"
+    } else if lower.contains("aarch64") || lower.contains("arm64") {
+        "aarch64"
+    } else if lower.contains("x86_64") || lower.contains("amd64") || lower.contains("x64") {
+        "x86_64"
+    } else if lower.contains("i686") || lower.contains("i386") || lower.contains("x86") {
+        "x86"
+    } else {
+        "unknown"
+    }
+}
+
+/// Score how well an asset architecture fits the target (None = cannot run)
+fn arch_score(asset_arch: &str, target: &UpdateTarget) -> Option<u8> {
+    if asset_arch == target.arch {
+        return Some(4);
+    }
+    match (asset_arch, target.os.as_str(), target.arch.as_str()) {
+        ("universal", "macos", _) => Some(3),
+        ("unknown", _, _) => Some(2),
+        // Rosetta 2 / Windows on ARM emulation can run x86_64 builds
+        ("x86_64", "macos", "aarch64") | ("x86_64", "windows", "aarch64") => Some(1),
+        // 64-bit Windows runs 32-bit installers
+        ("x86", "windows", "x86_64") => Some(1),
+        _ => None,
+    }
+}
+
+/// Score the package type for the target OS, higher is preferred
+fn package_score(package: &str, target: &UpdateTarget) -> u8 {
+    match target.os.as_str() {
+        "linux" => match (package, target.linux_package.as_deref()) {
+            ("deb", Some("deb")) | ("rpm", Some("rpm")) => 4,
+            ("appimage", _) => 3,
+            ("deb", _) => 2,
+            ("rpm", _) => 1,
+            _ => 0,
+        },
+        // NSIS setup installs per-user without elevation, so prefer it over MSI
+        "windows" => match package {
+            "exe" => 2,
+            "msi" => 1,
+            _ => 0,
+        },
+        "macos" => match package {
+            "dmg" => 2,
+            "app.tar.gz" => 1,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// Rank the release assets installable on the target, best match first
+/// Ordering is deterministic: architecture fit, then package preference, then file name
+pub fn rank_release_assets(assets: &[Value], target: &UpdateTarget) -> Vec<ReleaseAsset> {
+    let mut ranked: Vec<(u8, u8, ReleaseAsset)> = assets
+        .iter()
+        .filter_map(|asset| {
+            let name = asset.get("name").and_then(|v| v.as_str())?;
+            let download_url = asset.get("browser_download_url").and_then(|v| v.as_str())?;
+            let (os, package) = classify_asset_package(name)?;
+            if os != target.os {
+                return None;
+            }
+            let arch = detect_asset_arch(name);
+            let arch_rank = arch_score(arch, target)?;
+
+            Some((
+                arch_rank,
+                package_score(package, target),
+                ReleaseAsset {
+                    name: name.to_string(),
+                    download_url: download_url.to_string(),
+                    size: asset.get("size").and_then(|v| v.as_u64()).unwrap_or(0),
+                    os: os.to_string(),
+                    arch: arch.to_string(),
+                    package: package.to_string(),
+                    checksum_url: find_checksum_url(assets, name),
+                },
+            ))
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| b.1.cmp(&a.1))
+            .then_with(|| a.2.name.cmp(&b.2.name))
+    });
+    ranked.into_iter().map(|(_, _, asset)| asset).collect()
+}
+
+/// File name of the `.sha256` sidecar published next to `asset_name` (lowercase)
+fn sidecar_name(asset_name: &str) -> String {
+    format!("{}.sha256", asset_name).to_ascii_lowercase()
+}
+
+/// Whether `checksum_url` points at the `<asset>.sha256` sidecar of `asset_name`
+/// rather than at a combined listing
+fn is_sidecar_checksum(checksum_url: &str, asset_name: &str) -> bool {
+    checksum_url
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.to_ascii_lowercase() == sidecar_name(asset_name))
+}
+
+/// Find the checksum file for an asset: a `<asset>.sha256` sidecar or a combined listing
+fn find_checksum_url(assets: &[Value], asset_name: &str) -> Option<String> {
+    let sidecar_name = sidecar_name(asset_name);
+    let url_for = |wanted: &dyn Fn(&str) -> bool| {
+        assets.iter().find_map(|asset| {
+            let name = asset.get("name").and_then(|v| v.as_str())?;
+            if wanted(&name.to_ascii_lowercase()) {
+                asset
+                    .get("browser_download_url")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            } else {
+                None
+            }
+        })
+    };
+
+    url_for(&|name| name == sidecar_name)
+        .or_else(|| url_for(&|name| CHECKSUM_LISTING_NAMES.contains(&name)))
+}
+
+/// GitHub replaces spaces in uploaded asset names with dots
+fn normalize_asset_name(name: &str) -> String {
+    name.trim().trim_start_matches('*').replace(' ', ".")
+}
+
+/// Extract the expected SHA-256 (lowercase hex) for `file_name` from a checksum file
+/// Accepts `sha256sum` output (`<hash>  <file>` or `<hash> *<file>`); a bare hash without a
+/// file name is only accepted from the `sidecar` published for `file_name` itself
+pub fn parse_expected_checksum(content: &str, file_name: &str, sidecar: bool) -> Option<String> {
+    let wanted = normalize_asset_name(file_name);
+    let mut entries: Vec<(String, Option<String>)> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (hash, name) = match line.split_once(char::is_whitespace) {
+            Some((hash, name)) => (hash, Some(normalize_asset_name(name))),
+            None => (line, None),
+        };
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+        entries.push((hash.to_ascii_lowercase(), name));
+    }
+
+    if let Some((hash, _)) = entries
+        .iter()
+        .find(|(_, name)| name.as_deref() == Some(wanted.as_str()))
+    {
+        return Some(hash.clone());
+    }
+
+    // A combined listing must name the file; only a sidecar may hold just the hash
+    match entries.as_slice() {
+        [(hash, None)] if sidecar => Some(hash.clone()),
+        _ => None,
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Download a release asset into `dest_dir` and verify it against its published SHA-256
+/// The file is written as `<name>.part` and only renamed into place once the checksum matches;
+/// assets without a published checksum are refused
+pub async fn download_verified_asset<F>(
+    asset: &ReleaseAsset,
+    dest_dir: &Path,
+    on_progress: F,
+) -> Result<PathBuf, String>
+where
+    F: Fn(u64, Option<u64>),
+{
+    let checksum_url = asset
+        .checksum_url
+        .as_ref()
+        .ok_or_else(|| format!("Release does not publish a SHA-256 checksum for {}", asset.name))?;
+
+    // Never trust path components coming from the release metadata
+    let file_name = Path::new(&asset.name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid asset name: {}", asset.name))?
+        .to_string();
+
+    tokio::fs::create_dir_all(dest_dir)
+        .await
+        .map_err(|e| format!("Failed to create download directory: {}", e))?;
+
+    let client = reqwest::Client::builder()
+        .user_agent("Copilot-Tracker-App")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    // Fetch the expected checksum first so an unverifiable download is never started
+    let checksum_content = client
+        .get(checksum_url)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| format!("Failed to download checksum file: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read checksum file: {}", e))?;
+    let sidecar = is_sidecar_checksum(checksum_url, &asset.name);
+    let expected = parse_expected_checksum(&checksum_content, &file_name, sidecar)
+        .ok_or_else(|| format!("No SHA-256 checksum listed for {}", file_name))?;
+
+    let mut response = client
+        .get(&asset.download_url)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| format!("Failed to download {}: {}", file_name, e))?;
+    let total = response
+        .content_length()
+        .or(if asset.size > 0 { Some(asset.size) } else { None });
+
+    let partial_path = dest_dir.join(format!("{}.part", file_name));
+    let mut file = tokio::fs::File::create(&partial_path)
+        .await
+        .map_err(|e| format!("Failed to create download file: {}", e))?;
+
+    let mut hasher = Sha256::new();
+    let mut downloaded: u64 = 0;
+    let mut last_reported: u64 = 0;
+    on_progress(0, total);
+
+    loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                drop(file);
+                let _ = tokio::fs::remove_file(&partial_path).await;
+                return Err(format!("Download of {} interrupted: {}", file_name, e));
+            }
+        };
+        hasher.update(&chunk);
+        if let Err(e) = file.write_all(&chunk).await {
+            drop(file);
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return Err(format!("Failed to write download file: {}", e));
+        }
+        downloaded += chunk.len() as u64;
+        if downloaded - last_reported >= PROGRESS_REPORT_BYTES {
+            last_reported = downloaded;
+            on_progress(downloaded, total);
+        }
+    }
+
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to flush download file: {}", e))?;
+    file.sync_all()
+        .await
+        .map_err(|e| format!("Failed to sync download file: {}", e))?;
+    drop(file);
+    on_progress(downloaded, total);
+
+    let actual = to_hex(&hasher.finalize());
+    if actual != expected {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        log::error!(
+            "[Update] Checksum mismatch for {}: expected {}, got {}",
+            file_name,
+            expected,
+            actual
+        );
+        return Err(format!(
+            "Checksum mismatch for {}: the download was discarded",
+            file_name
+        ));
+    }
+
+    let final_path = dest_dir.join(&file_name);
+    tokio::fs::rename(&partial_path, &final_path)
+        .await
+        .map_err(|e| format!("Failed to move verified download into place: {}", e))?;
+
+    // AppImages are run directly, so they need the executable bit
+    #[cfg(unix)]
+    if asset.package == "appimage" {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&final_path, std::fs::Permissions::from_mode(0o755));
+    }
+
+    log::info!("[Update] Downloaded and verified {} ({} bytes)", file_name, downloaded);
+    Ok(final_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn release(tag: &str, prerelease: bool, draft: bool) -> Value {
+        json!({ "tag_name": tag, "prerelease": prerelease, "draft": draft })
+    }
+
+    fn target(os: &str, arch: &str, linux_package: Option<&str>) -> UpdateTarget {
+        UpdateTarget {
+            os: os.to_string(),
+            arch: arch.to_string(),
+            linux_package: linux_package.map(str::to_string),
+        }
+    }
+
+    fn assets(names: &[&str]) -> Vec<Value> {
+        names
+            .iter()
+            .map(|name| json!({ "name": name, "browser_download_url": format!("https://example.com/{}", name) }))
+            .collect()
+    }
+
+    fn ranked_names(names: &[&str], target: &UpdateTarget) -> Vec<String> {
+        rank_release_assets(&assets(names), target)
+            .into_iter()
+            .map(|asset| asset.name)
+            .collect()
+    }
+
+    fn selected_tag(releases: &[Value], channel: &str) -> Option<String> {
+        select_release_for_channel(releases, channel)
+            .and_then(|r| r["tag_name"].as_str().map(str::to_string))
+    }
+
+    #[test]
+    fn skips_drafts_on_every_channel() {
+        let releases = [release("v2.6.0", false, true), release("v2.5.0", false, false)];
+        assert_eq!(selected_tag(&releases, "stable").as_deref(), Some("v2.5.0"));
+        assert_eq!(selected_tag(&releases, "beta").as_deref(), Some("v2.5.0"));
+        assert_eq!(selected_tag(&releases[..1], "beta"), None);
+    }
+
+    #[test]
+    fn stable_skips_prerelease_flags_and_tags() {
+        let releases = [
+            release("v2.7.0", true, false),
+            release("v2.6.0-beta.1", false, false),
+            release("v2.5.0", false, false),
+        ];
+        assert_eq!(selected_tag(&releases, "stable").as_deref(), Some("v2.5.0"));
+        // Unknown channels are treated as stable
+        assert_eq!(selected_tag(&releases, "nightly").as_deref(), Some("v2.5.0"));
+        assert_eq!(selected_tag(&releases, "beta").as_deref(), Some("v2.7.0"));
+
+        let beta_only = [release("2.6.0-beta.2", true, false), release("not-a-version", false, false)];
+        assert_eq!(selected_tag(&beta_only, "stable"), None);
+        assert_eq!(selected_tag(&beta_only, "beta").as_deref(), Some("2.6.0-beta.2"));
+    }
+
+    #[test]
+    fn picks_the_highest_version_not_the_first_listed() {
+        let releases = [
+            release("v2.4.1", false, false),
+            release("v2.10.0", false, false),
+            release("v2.9.3", false, false),
+            release("v2.10.0-beta.1", true, false),
+        ];
+        assert_eq!(selected_tag(&releases, "stable").as_deref(), Some("v2.10.0"));
+        // A release outranks its own pre-releases
+        assert_eq!(selected_tag(&releases, "beta").as_deref(), Some("v2.10.0"));
+        assert_eq!(parse_release_version(" v2.10.0 "), semver::Version::parse("2.10.0").ok());
+    }
+
+    #[test]
+    fn detects_architectures_from_asset_names() {
+        assert_eq!(detect_asset_arch("Copilot.Tracker_2.5.0_x64-setup.exe"), "x86_64");
+        assert_eq!(detect_asset_arch("copilot-tracker_2.5.0_amd64.deb"), "x86_64");
+        assert_eq!(detect_asset_arch("copilot-tracker-2.5.0-1.x86_64.rpm"), "x86_64");
+        assert_eq!(detect_asset_arch("Copilot.Tracker_2.5.0_x86-setup.exe"), "x86");
+        assert_eq!(detect_asset_arch("copilot-tracker-2.5.0-1.i686.rpm"), "x86");
+        assert_eq!(detect_asset_arch("Copilot.Tracker_2.5.0_universal.dmg"), "universal");
+        assert_eq!(detect_asset_arch("Copilot.Tracker_2.5.0_aarch64.dmg"), "aarch64");
+        assert_eq!(detect_asset_arch("copilot-tracker_2.5.0_arm64.deb"), "aarch64");
+        assert_eq!(detect_asset_arch("Copilot.Tracker.app.tar.gz"), "unknown");
+    }
+
+    #[test]
+    fn prefers_native_then_universal_then_rosetta_on_apple_silicon() {
+        let names = [
+            "Copilot.Tracker_2.5.0_x64.dmg",
+            "Copilot.Tracker_2.5.0_universal.dmg",
+            "Copilot.Tracker_aarch64.app.tar.gz",
+            "Copilot.Tracker_2.5.0_x64-setup.exe",
+        ];
+        assert_eq!(
+            ranked_names(&names, &target("macos", "aarch64", None)),
+            [
+                "Copilot.Tracker_aarch64.app.tar.gz",
+                "Copilot.Tracker_2.5.0_universal.dmg",
+                "Copilot.Tracker_2.5.0_x64.dmg",
+            ]
+        );
+        // Intel Macs cannot run Apple Silicon builds
+        let names = ["Copilot.Tracker_2.5.0_aarch64.dmg", "Copilot.Tracker_2.5.0_universal.dmg"];
+        assert_eq!(
+            ranked_names(&names, &target("macos", "x86_64", None)),
+            ["Copilot.Tracker_2.5.0_universal.dmg"]
+        );
+    }
+
+    #[test]
+    fn ranks_windows_installers_by_arch_then_package() {
+        let names = [
+            "Copilot.Tracker_2.5.0_x86-setup.exe",
+            "Copilot.Tracker_2.5.0_x64_en-US.msi",
+            "Copilot.Tracker_2.5.0_x64-setup.exe",
+        ];
+        assert_eq!(
+            ranked_names(&names, &target("windows", "x86_64", None)),
+            [
+                "Copilot.Tracker_2.5.0_x64-setup.exe",
+                "Copilot.Tracker_2.5.0_x64_en-US.msi",
+                "Copilot.Tracker_2.5.0_x86-setup.exe",
+            ]
+        );
+        assert_eq!(
+            ranked_names(&names, &target("windows", "x86", None)),
+            ["Copilot.Tracker_2.5.0_x86-setup.exe"]
+        );
+    }
+
+    #[test]
+    fn prefers_the_detected_linux_package_type() {
+        let names = [
+            "copilot-tracker_2.5.0_amd64.deb",
+            "copilot-tracker-2.5.0-1.x86_64.rpm",
+            "copilot-tracker_2.5.0_amd64.AppImage",
+            "copilot-tracker_2.5.0_amd64.AppImage.sig",
+            "copilot-tracker-2.5.0-1.i686.rpm",
+        ];
+        assert_eq!(
+            ranked_names(&names, &target("linux", "x86_64", Some("rpm"))),
+            [
+                "copilot-tracker-2.5.0-1.x86_64.rpm",
+                "copilot-tracker_2.5.0_amd64.AppImage",
+                "copilot-tracker_2.5.0_amd64.deb",
+            ]
+        );
+        assert_eq!(
+            ranked_names(&names, &target("linux", "x86_64", None)),
+            [
+                "copilot-tracker_2.5.0_amd64.AppImage",
+                "copilot-tracker_2.5.0_amd64.deb",
+                "copilot-tracker-2.5.0-1.x86_64.rpm",
+            ]
+        );
+    }
+
+    #[test]
+    fn attaches_checksum_files() {
+        let names = [
+            "copilot-tracker_2.5.0_amd64.deb",
+            "copilot-tracker_2.5.0_amd64.deb.sha256",
+            "copilot-tracker_2.5.0_amd64.AppImage",
+            "SHA256SUMS.txt",
+        ];
+        let ranked = rank_release_assets(&assets(&names), &target("linux", "x86_64", Some("deb")));
+        assert_eq!(
+            ranked[0].checksum_url.as_deref(),
+            Some("https://example.com/copilot-tracker_2.5.0_amd64.deb.sha256")
+        );
+        assert_eq!(ranked[1].checksum_url.as_deref(), Some("https://example.com/SHA256SUMS.txt"));
+    }
+
+    const HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+    const OTHER_HASH: &str = "60303AE22B998861BCE3B28F33EEC1BE758A213C86C93C076DBE9F558C11C752";
+
+    #[test]
+    fn reads_sidecar_checksums() {
+        let asset = "copilot-tracker_2.5.0_amd64.deb";
+        assert!(is_sidecar_checksum("https://example.com/v2.5.0/copilot-tracker_2.5.0_amd64.deb.SHA256", asset));
+        assert!(!is_sidecar_checksum("https://example.com/v2.5.0/SHA256SUMS", asset));
+
+        // Bare hash, and `sha256sum` output naming the file
+        assert_eq!(parse_expected_checksum(&format!("{}\n", HASH), asset, true).as_deref(), Some(HASH));
+        let named = format!("{}  {}\n", HASH, asset);
+        assert_eq!(parse_expected_checksum(&named, asset, true).as_deref(), Some(HASH));
+        // A sidecar naming another file does not vouch for this one
+        let renamed = format!("{}  other.deb\n", HASH);
+        assert_eq!(parse_expected_checksum(&renamed, asset, true), None);
+        assert_eq!(parse_expected_checksum("not a hash", asset, true), None);
+    }
+
+    #[test]
+    fn reads_binary_mode_entries() {
+        let content = format!("# sha256sum -b\n{} *Copilot Tracker_2.5.0_x64-setup.exe\n", OTHER_HASH);
+        assert_eq!(
+            parse_expected_checksum(&content, "Copilot.Tracker_2.5.0_x64-setup.exe", false),
+            Some(OTHER_HASH.to_ascii_lowercase())
+        );
+    }
+
+    #[test]
+    fn combined_listings_must_name_the_file() {
+        let listing = format!(
+            "{}  copilot-tracker_2.5.0_amd64.deb\n{}  copilot-tracker_2.5.0_amd64.AppImage\n",
+            HASH, OTHER_HASH
+        );
+        assert_eq!(
+            parse_expected_checksum(&listing, "copilot-tracker_2.5.0_amd64.AppImage", false),
+            Some(OTHER_HASH.to_ascii_lowercase())
+        );
+        assert_eq!(parse_expected_checksum(&listing, "copilot-tracker-2.5.0-1.x86_64.rpm", false), None);
+
+        // A single entry in a listing is not a fallback for other assets
+        let single = format!("{}  copilot-tracker_2.5.0_amd64.deb\n", HASH);
+        assert_eq!(parse_expected_checksum(&single, "copilot-tracker_2.5.0_amd64.AppImage", false), None);
+        assert_eq!(parse_expected_checksum(HASH, "copilot-tracker_2.5.0_amd64.AppImage", false), None);
+    }
+}
@@ -1,6 +1,12 @@
-use crate::store::StoreManager;
-use crate::auth::UsageHistoryRow;
-use chrono::Datelike;
+use crate::accounts::{self, AccountStores, DEFAULT_ACCOUNT_ID};
+use crate::backoff::PollingHealth;
+use crate::billing::BillingCycle;
+use crate::billing_entity::{BillingEntity, PERSONAL_ENTITY_KEY};
+use crate::calendar::{self, Clock, SystemClock};
+use crate::store::{AppSettings, StoreManager, UsageCache};
+use crate::usage_query::{UsagePeriod, UsageQuery};
+use crate::usage_source::{self, UsageSource, WebviewUsageSource};
+use crate::auth::{ExtractionResult, SchemaDriftError, UsageData, UsageHistoryRow, UsageModelRow};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::time::Duration;
@@ -11,9 +17,42 @@ pub struct UsageSummary {
     pub limit: u32,
     pub remaining: u32,
     pub percentage: f32,
+    /// When the usage was fetched from GitHub; 0 when it never was
     pub timestamp: i64,
 }
 
+/// Refresh intervals after which usage counts as stale
+pub const STALE_AFTER_INTERVALS: i64 = 3;
+/// Usage younger than this is never stale, however short the refresh interval
+pub const MIN_STALE_AFTER_SECS: i64 = 15 * 60;
+
+/// Age in seconds after which usage refreshed every `refresh_interval` seconds is stale
+pub fn stale_after_secs(refresh_interval: u32) -> i64 {
+    (refresh_interval as i64 * STALE_AFTER_INTERVALS).max(MIN_STALE_AFTER_SECS)
+}
+
+/// Whether usage fetched at `fetched_at` is stale at `now`; usage never fetched is not
+pub fn is_stale(fetched_at: i64, refresh_interval: u32, now: i64) -> bool {
+    fetched_at > 0 && now - fetched_at > stale_after_secs(refresh_interval)
+}
+
+/// Age for the tray menu: "40 minutes", "1 hour", "5 hours", "2 days"
+pub fn format_age(age_secs: i64) -> String {
+    let (count, unit) = match age_secs.max(0) {
+        secs if secs < 3600 => (secs / 60, "minute"),
+        secs if secs < 2 * 86400 => (secs / 3600, "hour"),
+        secs => (secs / 86400, "day"),
+    };
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+impl UsageSummary {
+    /// Whether the summary is stale at `now` for the given refresh interval
+    pub fn is_stale(&self, refresh_interval: u32, now: i64) -> bool {
+        is_stale(self.timestamp, refresh_interval, now)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageHistory {
     pub entries: Vec<UsageEntry>,
@@ -28,6 +67,9 @@ pub struct UsageEntry {
     pub billed_requests: u32,
     pub gross_amount: f64,
     pub billed_amount: f64,
+    /// ISO 4217 code of the amounts, when known
+    #[serde(default)]
+    pub currency: Option<String>,
     #[serde(default)]
     pub models: Vec<UsageModel>,
 }
@@ -41,6 +83,15 @@ pub struct UsageModel {
     pub billed_amount: f64,
 }
 
+/// Point-in-time usage recorded on every successful poll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSnapshot {
+    pub timestamp: i64,
+    pub used: u32,
+    pub limit: u32,
+    pub billed_amount: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsagePayload {
     pub summary: UsageSummary,
@@ -56,7 +107,62 @@ pub struct UsagePrediction {
     pub days_used_for_prediction: u32,
 }
 
+/// Totals of one billing cycle, kept for month-over-month comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleUsage {
+    pub cycle_key: String,
+    /// First day of the cycle, "YYYY-MM-DD"
+    pub start_date: String,
+    /// Day after the last day of the cycle, "YYYY-MM-DD"
+    pub end_date: String,
+    pub used: u32,
+    pub limit: u32,
+    pub billed_amount: f64,
+    pub currency: Option<String>,
+    pub fetched_at: i64,
+}
+
+/// The current billing cycle next to the previous one, when it has been fetched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleComparison {
+    pub current: CycleUsage,
+    pub previous: Option<CycleUsage>,
+}
+
+/// Usage over the period of a `UsageQuery`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodUsage {
+    pub query: UsageQuery,
+    /// First day of the period, "YYYY-MM-DD"
+    pub start_date: String,
+    /// Day after the last day of the period, "YYYY-MM-DD"
+    pub end_date: String,
+    /// Daily rows, newest first; empty for queries grouped by model
+    pub days: Vec<UsageEntry>,
+    /// Per-model totals over the period
+    pub models: Vec<UsageModel>,
+    pub used: u32,
+    pub limit: u32,
+    pub billed_amount: f64,
+    pub currency: Option<String>,
+}
+
+/// Result of a usage refresh
+#[derive(Debug, Clone)]
+pub struct RefreshOutcome {
+    pub summary: UsageSummary,
+    /// Whether the summary came from the source rather than the cache
+    pub live: bool,
+    /// Set when the usage table could not be read because its columns changed
+    pub schema_drift: Option<SchemaDriftError>,
+    /// Set when this refresh found that the GitHub session has just expired
+    pub session_expired: bool,
+}
+
 pub struct UsageManager {
+    /// Data source; `None` uses the source configured for the app passed to `fetch_usage`
+    source: Option<Box<dyn UsageSource>>,
+    clock: Box<dyn Clock>,
 }
 
 impl Default for UsageManager {
@@ -67,112 +173,472 @@ impl Default for UsageManager {
 
 impl UsageManager {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            source: None,
+            clock: Box::new(SystemClock),
+        }
     }
 
-    /// Fetch and update usage data using hidden webview extraction
+    /// Use `source` instead of the hidden webview
+    pub fn with_source(mut self, source: Box<dyn UsageSource>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Use `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Fetch and update usage data, then notify the frontend and tray
     pub async fn fetch_usage(
         &mut self,
         app: &AppHandle,
     ) -> Result<UsageSummary, String> {
-        log::info!("Starting usage fetch with hidden webview extraction...");
+        log::info!("Starting usage fetch...");
 
-        // Create auth manager for extraction
-        let mut auth_manager = crate::auth::AuthManager::new();
-        
-        // Perform hidden extraction
-        match auth_manager.perform_extraction(app).await {
-            Ok(result) => {
-                if let Some(error) = result.error {
-                    log::warn!("Hidden extraction completed with error: {}", error);
-                    // Fall back to cached data on error
-                    let summary = Self::get_cached_usage(app)?;
-                    log::info!("Fallback: Emitting usage:updated with cached data: used={}, limit={}", summary.used, summary.limit);
-                    let _ = app.emit("usage:updated", &summary);
-                    return Ok(summary);
+        let configured;
+        let source: &dyn UsageSource = match &self.source {
+            Some(source) => source.as_ref(),
+            None => {
+                configured = usage_source::configured_source(app);
+                configured.as_ref()
+            }
+        };
+
+        let store = app.state::<StoreManager>();
+        let outcome = self.refresh(&store, source).await?;
+        // A successful manual refresh ends a backoff of background polling
+        if outcome.live {
+            if let Some(health) = app.try_state::<PollingHealth>() {
+                if health.record_success() {
+                    let _ = app.emit("polling:status", health.status());
                 }
+            }
+        }
+        self.publish(app, &outcome);
+        if self.source.is_none() {
+            self.refresh_entities(app).await;
+        }
 
-                // Process extracted data
-                if let Some(customer_id) = result.customer_id {
-                    let store = app.state::<crate::store::StoreManager>();
-                    let _ = store.set_customer_id(customer_id);
-
-                    if let Some(usage) = result.usage_data {
-                        let used = usage.discount_quantity as u32;
-                        let limit = usage.user_premium_request_entitlement as u32;
-                        
-                        log::info!("Extracted usage: {}/{} ({}%)", used, limit, 
-                            if limit > 0 { (used as f32 / limit as f32) * 100.0 } else { 0.0 });
-                        
-                        let _ = store.set_usage(used, limit);
-
-                        // Update cache
-                        let cache = crate::store::UsageCache {
-                            customer_id,
-                            net_quantity: usage.net_quantity,
-                            discount_quantity: usage.discount_quantity,
-                            user_premium_request_entitlement: usage.user_premium_request_entitlement,
-                            filtered_user_premium_request_entitlement: usage.filtered_user_premium_request_entitlement,
-                            net_billed_amount: usage.net_billed_amount,
-                            timestamp: chrono::Utc::now().timestamp(),
-                        };
-                        store.set_usage_cache(cache);
-
-                        // Save history if available
-                        if let Some(rows) = result.usage_history {
-                            let entries = Self::map_history_rows(&rows);
-                            store.set_usage_history(entries);
-                        }
+        Ok(outcome.summary)
+    }
 
-                        let summary = UsageSummary {
-                            used,
-                            limit,
-                            remaining: limit.saturating_sub(used),
-                            percentage: if limit > 0 { (used as f32 / limit as f32) * 100.0 } else { 0.0 },
-                            timestamp: chrono::Utc::now().timestamp(),
-                        };
-
-                        // Emit full payload
-                        let history = Self::get_cached_history(app);
-                        let store = app.state::<crate::store::StoreManager>();
-                        let settings = store.get_settings();
-                        let prediction = Self::predict_usage_from_history(&history, used, limit, settings.prediction_period);
-                        
-                        let payload = UsagePayload {
-                            summary: summary.clone(),
-                            history,
-                            prediction,
-                        };
-                        
-                        log::info!("Emitting usage:data event with used={}, limit={}", used, limit);
-                        let _ = app.emit("usage:data", payload);
-                        log::info!("Emitting usage:updated event with used={}, limit={} (tray should update)", used, limit);
-                        let _ = app.emit("usage:updated", &summary);
-                        
-                        return Ok(summary);
-                    }
+    /// Poll every signed-in account other than the default one into its own store and
+    /// emit `accounts:updated` with the usage of all accounts
+    /// These accounts are always read through the webview; the REST token belongs to the
+    /// default account
+    pub async fn poll_accounts(&self, app: &AppHandle) {
+        let (Some(store), Some(stores)) = (app.try_state::<StoreManager>(), app.try_state::<AccountStores>()) else {
+            return;
+        };
+        let profiles = store.get_settings().accounts;
+        let others: Vec<_> = profiles.iter().filter(|p| p.id != DEFAULT_ACCOUNT_ID).collect();
+        if others.is_empty() {
+            return;
+        }
+
+        for profile in others {
+            let account_store = match stores.open(&profile.id) {
+                Ok(store) => store,
+                Err(e) => {
+                    log::error!("[Background Polling] Failed to open account {}: {}", profile.id, e);
+                    continue;
+                }
+            };
+            let source = WebviewUsageSource::new(app.clone()).with_account(&profile.id);
+            if let Some(outcome) = self.poll(&account_store, &source).await {
+                if outcome.session_expired {
+                    Self::report_session_expired(app, &profile.id);
                 }
+                log::info!(
+                    "[Background Polling] Account {} updated: {}/{}",
+                    profile.id,
+                    outcome.summary.used,
+                    outcome.summary.limit
+                );
+            }
+        }
+        let _ = app.emit("accounts:updated", accounts::account_usages(app));
+    }
+
+    /// Fetch every selected organization and enterprise and emit `usage:entities` with the
+    /// usage of all selected entities; failures are logged and keep the entity's last cache
+    pub async fn refresh_entities(&self, app: &AppHandle) {
+        let Some(store) = app.try_state::<StoreManager>() else {
+            return;
+        };
+        let entities: Vec<BillingEntity> = store
+            .selected_billing_entities()
+            .into_iter()
+            .filter(|entity| !entity.is_personal())
+            .collect();
+
+        for entity in &entities {
+            let source = usage_source::configured_entity_source(app, entity);
+            if let Err(e) = self.refresh_entity(&store, entity, source.as_ref()).await {
+                log::warn!("Failed to fetch usage of {}: {}", entity.key(), e);
+            }
+        }
+        let _ = app.emit("usage:entities", store.get_usage_caches());
+    }
+
+    /// Fetch the current cycle of `entity` from `source` into its own usage cache
+    /// Only the cache is written; history, snapshots and the tray follow the personal account
+    pub async fn refresh_entity(
+        &self,
+        store: &StoreManager,
+        entity: &BillingEntity,
+        source: &dyn UsageSource,
+    ) -> Result<UsageCache, String> {
+        let result = source.fetch(&UsageQuery::default()).await?;
+        if let Some(error) = result.error {
+            return Err(error);
+        }
+        let customer_id = result.customer_id.ok_or("No customer ID was extracted")?;
+        let usage = result.usage_data.ok_or("No usage data was extracted")?;
+
+        let cache = Self::usage_cache(entity.key(), customer_id, &usage, self.clock.now().timestamp());
+        log::info!(
+            "Usage of {}: {}/{}",
+            cache.entity,
+            cache.discount_quantity,
+            cache.user_premium_request_entitlement
+        );
+        store.set_usage_cache(cache.clone());
+        Ok(cache)
+    }
 
-                // No data extracted, use cache
-                let summary = Self::get_cached_usage(app)?;
-                log::info!("No data extracted: Emitting usage:updated with cached data: used={}, limit={}", summary.used, summary.limit);
-                let _ = app.emit("usage:updated", &summary);
-                Ok(summary)
+    fn usage_cache(entity: String, customer_id: u64, usage: &UsageData, timestamp: i64) -> UsageCache {
+        UsageCache {
+            entity,
+            customer_id,
+            net_quantity: usage.net_quantity,
+            discount_quantity: usage.discount_quantity,
+            user_premium_request_entitlement: usage.user_premium_request_entitlement,
+            filtered_user_premium_request_entitlement: usage.filtered_user_premium_request_entitlement,
+            net_billed_amount: usage.net_billed_amount,
+            timestamp,
+        }
+    }
+
+    /// Fetch from `source` and store the result, falling back to cached usage when the
+    /// source fails or returns no usage
+    pub async fn refresh(
+        &self,
+        store: &StoreManager,
+        source: &dyn UsageSource,
+    ) -> Result<RefreshOutcome, String> {
+        let cached = |reason: &str, schema_drift: Option<SchemaDriftError>| {
+            log::info!("{}: using cached usage data", reason);
+            RefreshOutcome {
+                summary: self.cached_summary(store),
+                live: false,
+                schema_drift,
+                session_expired: false,
+            }
+        };
+
+        match source.fetch(&UsageQuery::default()).await {
+            Ok(mut result) => {
+                let schema_drift = result.schema_drift.take();
+                if result.session_expired {
+                    // Only the first refresh to find the session expired reports it
+                    let newly_expired = store.mark_session_expired().unwrap_or_else(|e| {
+                        log::error!("Failed to save expired session: {}", e);
+                        false
+                    });
+                    return Ok(RefreshOutcome {
+                        session_expired: newly_expired,
+                        ..cached("Session expired", schema_drift)
+                    });
+                }
+                if let Some(error) = result.error.take() {
+                    log::warn!("Usage extraction completed with error: {}", error);
+                    return Ok(cached("Extraction error", schema_drift));
+                }
+
+                match self.apply_extraction(store, result) {
+                    Some(outcome) => Ok(RefreshOutcome { schema_drift, ..outcome }),
+                    None => Ok(cached("No data extracted", schema_drift)),
+                }
             }
             Err(e) => {
-                log::error!("Hidden extraction failed: {}", e);
-                // Fall back to cached data
-                let summary = Self::get_cached_usage(app)?;
-                log::info!("Extraction failed: Emitting usage:updated with cached data: used={}, limit={}", summary.used, summary.limit);
-                let _ = app.emit("usage:updated", &summary);
-                Ok(summary)
+                log::error!("Usage extraction failed: {}", e);
+                Ok(cached("Extraction failed", None))
             }
         }
     }
 
+    /// One background poll: refreshes only while signed in with a live session
+    pub async fn poll(&self, store: &StoreManager, source: &dyn UsageSource) -> Option<RefreshOutcome> {
+        if !store.is_authenticated() {
+            log::debug!("[Background Polling] Skipping - not authenticated");
+            return None;
+        }
+        if store.is_session_expired() {
+            log::debug!("[Background Polling] Skipping - session expired, waiting for sign-in");
+            return None;
+        }
+
+        match self.refresh(store, source).await {
+            Ok(outcome) => Some(outcome),
+            Err(e) => {
+                log::warn!("[Background Polling] Failed to fetch usage: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Store freshly extracted usage, snapshot and history
+    /// Returns `None` when the result carries no customer or usage data
+    pub fn apply_extraction(
+        &self,
+        store: &StoreManager,
+        mut result: ExtractionResult,
+    ) -> Option<RefreshOutcome> {
+        let customer_id = result.customer_id?;
+        let _ = store.set_customer_id(customer_id);
+        let now = self.clock.now().timestamp();
+
+        if let Some(entities) = result.billing_entities.take() {
+            log::info!("Found {} organizations and enterprises", entities.len());
+            if let Err(e) = store.set_billing_entities(entities) {
+                log::error!("Failed to save billing entities: {}", e);
+            }
+        }
+
+        // Save history if available
+        if let Some(rows) = result.usage_history {
+            log::info!("Extracted {} usage history rows", rows.len());
+            let entries = Self::map_history_rows(&rows);
+            store.set_usage_history(entries);
+        }
+
+        let usage = match result.usage_data {
+            Some(usage) => usage,
+            None => {
+                log::warn!("No usage data was extracted from GitHub API");
+                return None;
+            }
+        };
+
+        let used = usage.discount_quantity as u32;
+        let limit = usage.user_premium_request_entitlement as u32;
+
+        log::info!("Extracted usage: {}/{} ({}%) with extractor v{}", used, limit,
+            if limit > 0 { (used as f32 / limit as f32) * 100.0 } else { 0.0 },
+            result.extractor_version.map_or_else(|| "?".to_string(), |v| v.to_string()));
+        if used == 0 && limit == 0 {
+            log::warn!("Usage data shows 0/0 - API may have returned empty data");
+        }
+
+        let _ = store.set_usage(used, limit, now);
+        store.append_usage_snapshot(UsageSnapshot {
+            timestamp: now,
+            used,
+            limit,
+            billed_amount: usage.net_billed_amount,
+        });
+
+        // Update cache
+        store.set_usage_cache(Self::usage_cache(PERSONAL_ENTITY_KEY.to_string(), customer_id, &usage, now));
+
+        Some(RefreshOutcome {
+            summary: UsageSummary {
+                used,
+                limit,
+                remaining: limit.saturating_sub(used),
+                percentage: if limit > 0 { (used as f32 / limit as f32) * 100.0 } else { 0.0 },
+                timestamp: now,
+            },
+            live: true,
+            schema_drift: None,
+            session_expired: false,
+        })
+    }
+
+    /// Fetch usage for `query` without touching the live usage figures
+    /// Daily rows are merged into the stored history; a previous-cycle query also stores
+    /// that month's totals for `cycle_comparison`
+    pub async fn fetch_period(
+        &self,
+        store: &StoreManager,
+        source: &dyn UsageSource,
+        query: &UsageQuery,
+    ) -> Result<PeriodUsage, String> {
+        let now = self.clock.now();
+        let (start, end) = query.date_range(now);
+
+        let mut result = source.fetch(query).await?;
+        if let Some(error) = result.error.take() {
+            return Err(format!("Failed to fetch usage period: {}", error));
+        }
+        if let Some(drift) = result.schema_drift.take() {
+            return Err(format!("Failed to read usage table: {}", drift));
+        }
+
+        let rows = result.usage_history.unwrap_or_default();
+        let days: Vec<UsageEntry> = Self::map_history_rows(&rows)
+            .into_iter()
+            .filter(|entry| {
+                chrono::DateTime::from_timestamp(entry.timestamp, 0)
+                    .map(|dt| dt.date_naive())
+                    .is_some_and(|date| date >= start && date < end)
+            })
+            .collect();
+        if !days.is_empty() {
+            store.set_usage_history(days.clone());
+        }
+
+        let models = match &result.usage_models {
+            Some(rows) => Self::map_model_rows(rows),
+            None => Self::sum_models(&days),
+        };
+
+        // The usage card covers whole cycles only
+        let card = match query.period {
+            UsagePeriod::LastDays { .. } => None,
+            _ => result.usage_data,
+        };
+        let (used, limit, billed_amount) = match &card {
+            Some(usage) => (
+                usage.discount_quantity as u32,
+                usage.user_premium_request_entitlement as u32,
+                usage.net_billed_amount,
+            ),
+            None if !days.is_empty() => (
+                days.iter().map(|d| d.used).sum(),
+                0,
+                days.iter().map(|d| d.billed_amount).sum(),
+            ),
+            None => (
+                models.iter().map(|m| m.included_requests + m.billed_requests).sum(),
+                0,
+                models.iter().map(|m| m.billed_amount).sum(),
+            ),
+        };
+        let currency = days.iter().find_map(|d| d.currency.clone());
+
+        // The card totals cover the previous calendar month, not the anchor-day cycle
+        if query.period == UsagePeriod::PreviousCycle {
+            let previous = BillingCycle::calendar_month(now).previous();
+            store.set_cycle_usage(&CycleUsage {
+                cycle_key: previous.key(),
+                start_date: previous.start_date.to_string(),
+                end_date: previous.end_date.to_string(),
+                used,
+                limit,
+                billed_amount,
+                currency: currency.clone(),
+                fetched_at: now.timestamp(),
+            });
+        }
+
+        Ok(PeriodUsage {
+            query: *query,
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            days,
+            models,
+            used,
+            limit,
+            billed_amount,
+            currency,
+        })
+    }
+
+    /// Current month from the live usage, previous month from the stored totals
+    /// Both are calendar months, as the usage card reports them
+    pub fn cycle_comparison(&self, store: &StoreManager) -> CycleComparison {
+        let cycle = BillingCycle::calendar_month(self.clock.now());
+        let (used, limit) = store.get_usage();
+        let cache = store.get_usage_cache();
+
+        CycleComparison {
+            current: CycleUsage {
+                cycle_key: cycle.key(),
+                start_date: cycle.start_date.to_string(),
+                end_date: cycle.end_date.to_string(),
+                used,
+                limit,
+                billed_amount: cache.as_ref().map_or(0.0, |c| c.net_billed_amount),
+                currency: None,
+                fetched_at: cache.map_or(0, |c| c.timestamp),
+            },
+            previous: store.get_cycle_usage(&cycle.previous().key()),
+        }
+    }
+
+    /// Notify about a refresh: threshold alerts and the full payload for live data,
+    /// `usage:updated` (which the tray listens to) in every case
+    pub fn publish(&self, app: &AppHandle, outcome: &RefreshOutcome) {
+        let summary = &outcome.summary;
+
+        if let Some(drift) = &outcome.schema_drift {
+            Self::report_schema_drift(app, drift);
+        }
+        if outcome.session_expired {
+            Self::report_session_expired(app, DEFAULT_ACCOUNT_ID);
+        }
+
+        if outcome.live {
+            crate::notifications::check_usage_thresholds(app, summary.used, summary.limit);
+
+            let history = Self::get_cached_history(app);
+            let store = app.state::<StoreManager>();
+            let settings = store.get_settings();
+            let prediction = Self::predict_usage_from_history(
+                &history,
+                summary.used,
+                summary.limit,
+                &settings,
+                self.clock.as_ref(),
+            );
+
+            let payload = UsagePayload {
+                summary: summary.clone(),
+                history,
+                prediction,
+            };
+
+            log::info!("Emitting usage:data event with used={}, limit={}", summary.used, summary.limit);
+            let _ = app.emit("usage:data", payload);
+        }
+
+        log::info!("Emitting usage:updated event with used={}, limit={} (tray should update)", summary.used, summary.limit);
+        let _ = app.emit("usage:updated", summary);
+    }
+
+    /// Log a usage table format change and tell the frontend about it
+    pub fn report_schema_drift(app: &AppHandle, drift: &SchemaDriftError) {
+        log::error!("Usage history not updated: {}", drift);
+        let _ = app.emit("usage:schema-drift", drift);
+    }
+
+    /// Tell the frontend and tray that the session of `account` expired, and the user once
+    /// `auth:session-expired` carries the account id; the auth state is the default account's
+    pub fn report_session_expired(app: &AppHandle, account: &str) {
+        log::warn!(
+            "GitHub session of account {} expired, polling paused until the next sign-in",
+            account
+        );
+        if account == DEFAULT_ACCOUNT_ID {
+            let _ = app.emit("auth:state-changed", "expired");
+        }
+        let _ = app.emit("auth:session-expired", account);
+        crate::notifications::notify_session_expired(app, account);
+    }
+
     /// Get cached usage from store
     pub fn get_cached_usage(app: &AppHandle) -> Result<UsageSummary, String> {
         let store = app.state::<StoreManager>();
+        Ok(Self::new().cached_summary(&store))
+    }
+
+    /// Summary of the usage last stored, stamped with when it was fetched
+    pub fn cached_summary(&self, store: &StoreManager) -> UsageSummary {
         let (used, limit) = store.get_usage();
 
         let remaining = limit.saturating_sub(used);
@@ -182,33 +648,40 @@ impl UsageManager {
             0.0
         };
 
-        Ok(UsageSummary {
+        UsageSummary {
             used,
             limit,
             remaining,
             percentage,
-            timestamp: chrono::Utc::now().timestamp(),
-        })
+            timestamp: store.get_last_fetch_timestamp(),
+        }
     }
 
     pub fn get_cached_history(app: &AppHandle) -> Vec<UsageEntry> {
-        if let Some(store) = app.try_state::<StoreManager>() {
-            let history = store.get_usage_history();
-            if !history.is_empty() {
-                return history;
-            }
-            if let Some(cache) = store.get_usage_cache() {
-                return vec![UsageEntry {
-                    timestamp: cache.timestamp,
-                    used: cache.discount_quantity as u32,
-                    limit: cache.user_premium_request_entitlement as u32,
-                    included_requests: cache.discount_quantity as u32,
-                    billed_requests: cache.net_quantity.saturating_sub(cache.discount_quantity) as u32,
-                    gross_amount: cache.net_billed_amount,
-                    billed_amount: cache.net_billed_amount,
-                    models: vec![],
-                }];
-            }
+        match app.try_state::<StoreManager>() {
+            Some(store) => Self::cached_history(&store),
+            None => vec![],
+        }
+    }
+
+    /// Stored history of `store`, or a single entry from its usage cache when it has none
+    pub fn cached_history(store: &StoreManager) -> Vec<UsageEntry> {
+        let history = store.get_usage_history();
+        if !history.is_empty() {
+            return history;
+        }
+        if let Some(cache) = store.get_usage_cache() {
+            return vec![UsageEntry {
+                timestamp: cache.timestamp,
+                used: cache.discount_quantity as u32,
+                limit: cache.user_premium_request_entitlement as u32,
+                included_requests: cache.discount_quantity as u32,
+                billed_requests: cache.net_quantity.saturating_sub(cache.discount_quantity) as u32,
+                gross_amount: cache.net_billed_amount,
+                billed_amount: cache.net_billed_amount,
+                currency: None,
+                models: vec![],
+            }];
         }
         vec![]
     }
@@ -236,13 +709,7 @@ impl UsageManager {
                     }
                 };
                 
-                let models = row.models.iter().map(|m| UsageModel {
-                    name: m.name.clone(),
-                    included_requests: m.included_requests,
-                    billed_requests: m.billed_requests,
-                    gross_amount: m.gross_amount,
-                    billed_amount: m.billed_amount,
-                }).collect();
+                let models = Self::map_model_rows(&row.models);
 
                 UsageEntry {
                     timestamp,
@@ -252,6 +719,7 @@ impl UsageManager {
                     billed_requests: row.billed_requests,
                     gross_amount: row.gross_amount,
                     billed_amount: row.billed_amount,
+                    currency: row.currency.clone(),
                     models,
                 }
             })
@@ -260,41 +728,97 @@ impl UsageManager {
         entries
     }
 
+    fn map_model_rows(rows: &[UsageModelRow]) -> Vec<UsageModel> {
+        rows.iter()
+            .map(|m| UsageModel {
+                name: m.name.clone(),
+                included_requests: m.included_requests,
+                billed_requests: m.billed_requests,
+                gross_amount: m.gross_amount,
+                billed_amount: m.billed_amount,
+            })
+            .collect()
+    }
+
+    /// Per-model totals over `days`, in order of first appearance
+    fn sum_models(days: &[UsageEntry]) -> Vec<UsageModel> {
+        let mut totals: Vec<UsageModel> = Vec::new();
+        for model in days.iter().flat_map(|d| &d.models) {
+            match totals.iter_mut().find(|t| t.name == model.name) {
+                Some(total) => {
+                    total.included_requests += model.included_requests;
+                    total.billed_requests += model.billed_requests;
+                    total.gross_amount += model.gross_amount;
+                    total.billed_amount += model.billed_amount;
+                }
+                None => totals.push(model.clone()),
+            }
+        }
+        totals
+    }
+
     /// Start background usage polling with cancellation support
     /// Returns a channel sender that can be used to cancel the polling task
     pub fn start_polling(app: AppHandle, interval_seconds: u64) -> tokio::sync::mpsc::Sender<()> {
         let (cancel_tx, mut cancel_rx) = tokio::sync::mpsc::channel::<()>(1);
         
         tauri::async_runtime::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds));
-            
-            // Skip the first tick (immediate fire)
-            interval.tick().await;
+            let Some(health) = app.try_state::<PollingHealth>() else {
+                log::error!("[Background Polling] PollingHealth not available, not polling");
+                return;
+            };
+            let interval = Duration::from_secs(interval_seconds);
+            let mut status_changed = false;
 
             loop {
+                // Waits the interval while polls succeed and backs off while they fail
+                let delay = health.next_delay(interval);
+                let next_poll_at = chrono::Utc::now().timestamp() + delay.as_secs() as i64;
+                health.set_next_poll_at(Some(next_poll_at));
+                if std::mem::take(&mut status_changed) {
+                    let status = health.status();
+                    log::info!(
+                        "[Background Polling] Circuit {:?} after {} consecutive failures",
+                        status.state,
+                        status.consecutive_failures
+                    );
+                    let _ = app.emit("polling:status", status);
+                }
+
                 tokio::select! {
-                    _ = interval.tick() => {
+                    _ = tokio::time::sleep(delay) => {
                         // SAFETY: Only access StoreManager if it's available
                         // Use try_state to avoid panicking if state is not yet managed
                         match app.try_state::<StoreManager>() {
                             Some(store) => {
-                                if store.is_authenticated() {
-                                    // Create a new usage manager for this poll
-                                    let mut usage_manager = UsageManager::new();
-
-                                    if let Ok(summary) = usage_manager.fetch_usage(&app).await {
-                                        log::info!(
-                                            "[Background Polling] Usage updated: {}/{} ({}%)",
-                                            summary.used,
-                                            summary.limit,
-                                            summary.percentage
-                                        );
-                                    } else {
-                                        log::warn!("[Background Polling] Failed to fetch usage");
+                                // Create a new usage manager for this poll
+                                let usage_manager = UsageManager::new();
+                                let source = usage_source::configured_source(&app);
+
+                                let outcome = usage_manager.poll(&store, source.as_ref()).await;
+                                // Every failure moves the next poll, so the tray is told about each one
+                                status_changed = match &outcome {
+                                    Some(outcome) if outcome.live => health.record_success(),
+                                    // An expired session pauses polling until sign-in; retrying
+                                    // sooner or later would not help, so it is not a failure
+                                    Some(_) if store.is_session_expired() => false,
+                                    Some(_) => {
+                                        health.record_failure();
+                                        true
                                     }
-                                } else {
-                                    log::debug!("[Background Polling] Skipping - not authenticated");
+                                    None => false,
+                                };
+                                if let Some(outcome) = outcome {
+                                    usage_manager.publish(&app, &outcome);
+                                    log::info!(
+                                        "[Background Polling] Usage updated: {}/{} ({}%)",
+                                        outcome.summary.used,
+                                        outcome.summary.limit,
+                                        outcome.summary.percentage
+                                    );
+                                    usage_manager.refresh_entities(&app).await;
                                 }
+                                usage_manager.poll_accounts(&app).await;
                             }
                             None => {
                                 // StoreManager not yet available - skip this tick
@@ -302,7 +826,11 @@ impl UsageManager {
                             }
                         }
                     }
+                    _ = health.reset() => {
+                        log::info!("[Background Polling] Circuit closed by a manual refresh");
+                    }
                     _ = cancel_rx.recv() => {
+                        health.set_next_poll_at(None);
                         log::info!("[Background Polling] Cancelled");
                         break;
                     }
@@ -313,49 +841,42 @@ impl UsageManager {
         cancel_tx
     }
 
-    /// Predict end-of-month usage based on current trends
+    /// Predict end-of-cycle usage based on current trends
     pub fn predict_eom_usage(app: &AppHandle) -> Result<u32, String> {
         let store = app.state::<StoreManager>();
         let (used, _limit) = store.get_usage();
+        Ok(Self::project_cycle_usage(used, &store.get_settings(), &SystemClock))
+    }
 
-        // Simple prediction: assume linear usage
-        let now = chrono::Utc::now();
-        let current_day = now.day() as f32;
-
-        // Get days in month using naive date calculation
-        let days_in_month = if now.month() == 12 {
-            // January next year
-            31
-        } else {
-            // First day of next month minus first day of current month
-            let next_month = chrono::NaiveDate::from_ymd_opt(now.year(), now.month() + 1, 1)
-                .unwrap_or_else(|| chrono::NaiveDate::from_ymd_opt(now.year() + 1, 1, 1).unwrap());
-            let current_month =
-                chrono::NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap();
-            (next_month - current_month).num_days() as u32
-        };
-
-        if current_day == 0.0 {
-            return Ok(used);
-        }
+    /// Linear end-of-cycle projection from the average daily usage so far
+    pub fn project_cycle_usage(used: u32, settings: &AppSettings, clock: &dyn Clock) -> u32 {
+        let now = clock.now();
+        let cycle = BillingCycle::current(settings, clock);
+        let current_day = cycle.day_of_cycle(now) as f32;
 
         let daily_average = used as f32 / current_day;
-        let remaining_days = days_in_month as f32 - current_day;
+        let remaining_days = cycle.remaining_days(now) as f32;
         let predicted = used as f32 + (daily_average * remaining_days);
 
-        Ok(predicted as u32)
+        predicted as u32
     }
 
     pub fn predict_usage_from_history(
         history: &[UsageEntry],
         used: u32,
         limit: u32,
-        prediction_period: u32,
+        settings: &AppSettings,
+        clock: &dyn Clock,
     ) -> Option<UsagePrediction> {
         if history.is_empty() {
             return None;
         }
 
+        let prediction_period = settings.prediction_period;
+        let now = clock.now();
+        let cycle = BillingCycle::current(settings, clock);
+        let holidays = calendar::parse_holidays(&settings.holidays);
+
         // Get weights based on period
         let weights = match prediction_period {
             7 => vec![1.5, 1.5, 1.2, 1.2, 1.2, 1.0, 1.0],
@@ -401,9 +922,10 @@ impl UsageManager {
         for entry in &daily_data {
             let dt = chrono::DateTime::from_timestamp(entry.timestamp, 0)
                 .map(|dt| dt.date_naive())
-                .unwrap_or_else(|| chrono::Utc::now().date_naive());
+                .unwrap_or_else(|| now.date_naive());
             
-            let is_weekend = matches!(dt.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun);
+            // Holidays behave like weekends
+            let is_weekend = calendar::is_day_off(dt, &holidays);
             
             if is_weekend {
                 weekend_sum += entry.used as f64;
@@ -420,42 +942,10 @@ impl UsageManager {
         // If no weekday data, ratio is 1.0
         let weekend_ratio = if avg_weekday > 0.0 { avg_weekend / avg_weekday } else { 1.0 };
 
-        // 3. Calculate remaining days
-        let now = chrono::Utc::now();
-        let today = now.date_naive();
-        let current_day = now.day();
-        
-        let days_in_month = if now.month() == 12 {
-            31
-        } else {
-            let next_month = chrono::NaiveDate::from_ymd_opt(now.year(), now.month() + 1, 1)
-                .unwrap_or_else(|| chrono::NaiveDate::from_ymd_opt(now.year() + 1, 1, 1).unwrap());
-            let current_month =
-                chrono::NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap();
-            (next_month - current_month).num_days() as u32
-        };
-
-        let remaining_days = days_in_month.saturating_sub(current_day);
-        
-        // Count remaining weekdays and weekends
-        let mut remaining_weekdays = 0;
-        let mut remaining_weekends = 0;
-        
-        for i in 1..=remaining_days {
-            // Need to handle month overflow if we just add days? 
-            // Actually simpler: iterate and check. 
-            // Note: `today` is current day. We want remaining days effectively from tomorrow?
-            // predictor.ts: date.setDate(today.getDate() + i)
-            // So yes, starting from tomorrow.
-            
-            if let Some(date) = today.checked_add_days(chrono::Days::new(i as u64)) {
-                if matches!(date.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun) {
-                    remaining_weekends += 1;
-                } else {
-                    remaining_weekdays += 1;
-                }
-            }
-        }
+        // 3. Count remaining weekdays and days off, starting tomorrow, until the cycle resets
+        let remaining = calendar::count_days_between(cycle.date_of(now), cycle.end_date, &holidays);
+        let remaining_weekdays = remaining.weekdays;
+        let remaining_weekends = remaining.days_off;
 
         // 4. Predict total monthly usage
         // usage = current + (avg * weekdays) + (avg * ratio * weekends)
@@ -486,28 +976,523 @@ impl UsageManager {
     pub fn days_until_limit(app: &AppHandle) -> Result<Option<i64>, String> {
         let store = app.state::<StoreManager>();
         let (used, limit) = store.get_usage();
+        Ok(Self::project_days_until_limit(used, limit, &store.get_settings(), &SystemClock))
+    }
 
+    /// Days until `limit` is reached at the average daily rate of the current cycle
+    pub fn project_days_until_limit(
+        used: u32,
+        limit: u32,
+        settings: &AppSettings,
+        clock: &dyn Clock,
+    ) -> Option<i64> {
         if used >= limit {
-            return Ok(Some(0)); // Already at or over limit
+            return Some(0); // Already at or over limit
         }
 
         let remaining = (limit - used) as f32;
 
-        // Calculate daily average
-        let now = chrono::Utc::now();
-        let current_day = now.day() as f32;
+        // Calculate daily average over the elapsed part of the billing cycle
+        let cycle = BillingCycle::current(settings, clock);
+        let current_day = cycle.day_of_cycle(clock.now()) as f32;
+        let daily_average = used as f32 / current_day;
 
-        if current_day == 0.0 {
-            return Ok(None);
+        if daily_average == 0.0 {
+            return None; // Can't predict if no usage yet
         }
 
-        let daily_average = used as f32 / current_day;
+        Some((remaining / daily_average).ceil() as i64)
+    }
+}
 
-        if daily_average == 0.0 {
-            return Ok(None); // Can't predict if no usage yet
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::FixedClock;
+    use crate::test_support::TempDir;
+    use crate::usage_query::UsageGrouping;
+    use crate::usage_source::FixtureUsageSource;
+    use chrono::TimeZone;
+
+    const FIXTURE: &str = r#"{
+        "customer_id": 4242,
+        "usage_data": {
+            "net_billed_amount": 1.2,
+            "net_quantity": 30,
+            "discount_quantity": 120,
+            "user_premium_request_entitlement": 300,
+            "filtered_user_premium_request_entitlement": 300
+        },
+        "usage_history": [
+            {
+                "date": "2026-03-09 00:00:00 +0000 UTC",
+                "included_requests": 40,
+                "billed_requests": 0,
+                "gross_amount": 1.6,
+                "billed_amount": 0.0,
+                "models": []
+            },
+            {
+                "date": "2026-03-10 00:00:00 +0000 UTC",
+                "included_requests": 80,
+                "billed_requests": 0,
+                "gross_amount": 3.2,
+                "billed_amount": 0.0,
+                "models": []
+            }
+        ],
+        "error": null
+    }"#;
+
+    const PREVIOUS_CYCLE_FIXTURE: &str = r#"{
+        "customer_id": 4242,
+        "usage_data": {
+            "net_billed_amount": 2.5,
+            "net_quantity": 310,
+            "discount_quantity": 250,
+            "user_premium_request_entitlement": 300,
+            "filtered_user_premium_request_entitlement": 300
+        },
+        "usage_history": [
+            {
+                "date": "2026-02-27 00:00:00 +0000 UTC",
+                "included_requests": 30,
+                "billed_requests": 2,
+                "gross_amount": 1.28,
+                "billed_amount": 0.08,
+                "currency": "USD",
+                "models": [
+                    { "name": "GPT-4.1", "included_requests": 20, "billed_requests": 0, "gross_amount": 0.8, "billed_amount": 0.0 },
+                    { "name": "o3", "included_requests": 10, "billed_requests": 2, "gross_amount": 0.48, "billed_amount": 0.08 }
+                ]
+            },
+            {
+                "date": "2026-02-28 00:00:00 +0000 UTC",
+                "included_requests": 15,
+                "billed_requests": 0,
+                "gross_amount": 0.6,
+                "billed_amount": 0.0,
+                "currency": "USD",
+                "models": [
+                    { "name": "o3", "included_requests": 15, "billed_requests": 0, "gross_amount": 0.6, "billed_amount": 0.0 }
+                ]
+            },
+            {
+                "date": "2026-03-01 00:00:00 +0000 UTC",
+                "included_requests": 5,
+                "billed_requests": 0,
+                "gross_amount": 0.2,
+                "billed_amount": 0.0,
+                "models": []
+            }
+        ],
+        "error": null
+    }"#;
+
+    /// Store in a throwaway data directory, removed on drop
+    struct TempStore {
+        store: StoreManager,
+        _dir: TempDir,
+    }
+
+    impl TempStore {
+        fn new(name: &str) -> Self {
+            let dir = TempDir::new(name);
+            let store = StoreManager::new(dir.0.clone()).expect("store");
+            Self { store, _dir: dir }
+        }
+    }
+
+    fn manager() -> UsageManager {
+        UsageManager::new().with_clock(Box::new(clock_at(2026, 3, 10, 12)))
+    }
+
+    /// Fetch time of usage stored before a test refresh, an hour before `manager`'s clock
+    fn fetched_at() -> i64 {
+        clock_at(2026, 3, 10, 11).now().timestamp()
+    }
+
+    fn clock_at(y: i32, m: u32, d: u32, h: u32) -> FixedClock {
+        FixedClock(chrono::Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap())
+    }
+
+    fn day_entry(y: i32, m: u32, d: u32, used: u32) -> UsageEntry {
+        UsageEntry {
+            timestamp: chrono::Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap().timestamp(),
+            used,
+            limit: 0,
+            included_requests: used,
+            billed_requests: 0,
+            gross_amount: 0.0,
+            billed_amount: 0.0,
+            currency: None,
+            models: vec![],
+        }
+    }
+
+    #[test]
+    fn projects_linear_usage_through_december() {
+        let settings = AppSettings::default();
+
+        // Day 15 of 31: 10/day with 16 days left
+        let clock = clock_at(2025, 12, 15, 12);
+        assert_eq!(UsageManager::project_cycle_usage(150, &settings, &clock), 310);
+
+        // Last day of the year has nothing left to project
+        let clock = clock_at(2025, 12, 31, 23);
+        assert_eq!(UsageManager::project_cycle_usage(310, &settings, &clock), 310);
+    }
+
+    #[test]
+    fn projects_february_by_leap_year() {
+        let settings = AppSettings::default();
+
+        assert_eq!(UsageManager::project_cycle_usage(100, &settings, &clock_at(2024, 2, 10, 0)), 290);
+        assert_eq!(UsageManager::project_cycle_usage(100, &settings, &clock_at(2026, 2, 10, 0)), 280);
+    }
+
+    #[test]
+    fn projects_cycles_spanning_new_year() {
+        let settings = AppSettings {
+            billing_cycle_anchor_day: 15,
+            ..AppSettings::default()
+        };
+
+        // 10 Jan is day 27 of the 15 Dec - 14 Jan cycle
+        let clock = clock_at(2026, 1, 10, 0);
+        assert_eq!(UsageManager::project_cycle_usage(270, &settings, &clock), 310);
+    }
+
+    #[test]
+    fn days_until_limit_uses_cycle_average() {
+        let settings = AppSettings::default();
+        let clock = clock_at(2026, 3, 10, 8);
+
+        assert_eq!(UsageManager::project_days_until_limit(100, 300, &settings, &clock), Some(20));
+        assert_eq!(UsageManager::project_days_until_limit(300, 300, &settings, &clock), Some(0));
+        assert_eq!(UsageManager::project_days_until_limit(0, 300, &settings, &clock), None);
+    }
+
+    #[test]
+    fn history_prediction_weights_weekends_and_holidays() {
+        // Wed 17 - Tue 23 Dec, newest first, idle on the weekend
+        let history: Vec<UsageEntry> = (17..=23)
+            .rev()
+            .map(|d| day_entry(2025, 12, d, if d == 20 || d == 21 { 0 } else { 10 }))
+            .collect();
+        let clock = clock_at(2025, 12, 24, 12);
+
+        // 25-31 Dec leaves 5 weekdays at the weighted average of ~7.2/day
+        let settings = AppSettings::default();
+        let prediction =
+            UsageManager::predict_usage_from_history(&history, 240, 300, &settings, &clock).unwrap();
+        assert_eq!(prediction.predicted_monthly_requests, 276);
+        assert_eq!(prediction.confidence_level, "high");
+        assert_eq!(prediction.days_used_for_prediction, 7);
+
+        // Christmas as a holiday is predicted like a weekend day
+        let settings = AppSettings {
+            holidays: vec!["2025-12-25".to_string()],
+            ..AppSettings::default()
+        };
+        let prediction =
+            UsageManager::predict_usage_from_history(&history, 240, 300, &settings, &clock).unwrap();
+        assert_eq!(prediction.predicted_monthly_requests, 269);
+    }
+
+    #[test]
+    fn history_prediction_needs_history() {
+        let clock = clock_at(2026, 1, 1, 0);
+        let settings = AppSettings::default();
+        assert!(UsageManager::predict_usage_from_history(&[], 0, 300, &settings, &clock).is_none());
+    }
+
+    #[tokio::test]
+    async fn refresh_applies_fixture_data() {
+        let temp = TempStore::new("fixture");
+        let source = FixtureUsageSource::from_json(FIXTURE).unwrap();
+
+        let outcome = manager().refresh(&temp.store, &source).await.unwrap();
+
+        assert!(outcome.live);
+        assert_eq!(outcome.summary.used, 120);
+        assert_eq!(outcome.summary.limit, 300);
+        assert_eq!(outcome.summary.remaining, 180);
+        assert_eq!(outcome.summary.timestamp, clock_at(2026, 3, 10, 12).now().timestamp());
+        assert_eq!(temp.store.get_last_fetch_timestamp(), outcome.summary.timestamp);
+
+        assert_eq!(temp.store.get_customer_id(), Some(4242));
+        assert!(temp.store.is_authenticated());
+        assert_eq!(temp.store.get_usage(), (120, 300));
+        assert_eq!(temp.store.get_usage_cache().unwrap().net_quantity, 30);
+
+        let history = temp.store.get_usage_history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].used, 80);
+
+        let snapshots = temp.store.get_usage_snapshots(None);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].timestamp, outcome.summary.timestamp);
+        assert_eq!(source.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn entities_keep_separate_usage_caches() {
+        let temp = TempStore::new("entities");
+        let mut personal: ExtractionResult = serde_json::from_str(FIXTURE).unwrap();
+        personal.billing_entities = Some(vec![
+            BillingEntity::organization("acme", None),
+            BillingEntity::enterprise("acme-corp", None),
+        ]);
+        let manager = manager();
+        manager
+            .refresh(&temp.store, &FixtureUsageSource::new(vec![Ok(personal)]))
+            .await
+            .unwrap();
+        assert_eq!(temp.store.get_settings().billing_entities.len(), 3);
+
+        let org = BillingEntity::organization("acme", None);
+        temp.store
+            .set_selected_billing_entities(vec![org.key()])
+            .unwrap();
+        let mut org_result: ExtractionResult = serde_json::from_str(FIXTURE).unwrap();
+        org_result.customer_id = Some(99);
+        org_result.usage_data.as_mut().unwrap().discount_quantity = 900;
+        let cache = manager
+            .refresh_entity(&temp.store, &org, &FixtureUsageSource::new(vec![Ok(org_result)]))
+            .await
+            .unwrap();
+        assert_eq!(cache.entity, "organization/acme");
+
+        let caches = temp.store.get_usage_caches();
+        let summary: Vec<(&str, u64, u64)> = caches
+            .iter()
+            .map(|c| (c.entity.as_str(), c.customer_id, c.discount_quantity))
+            .collect();
+        assert_eq!(summary, [("personal", 4242, 120), ("organization/acme", 99, 900)]);
+        assert_eq!(temp.store.get_usage(), (120, 300));
+
+        let failing = FixtureUsageSource::new(vec![Err("Extraction timed out".to_string())]);
+        assert!(manager.refresh_entity(&temp.store, &org, &failing).await.is_err());
+        assert_eq!(temp.store.get_entity_usage_cache(&org.key()).unwrap().discount_quantity, 900);
+    }
+
+    #[test]
+    fn staleness_follows_the_refresh_interval() {
+        let now = 1_000_000;
+        assert_eq!(stale_after_secs(60), MIN_STALE_AFTER_SECS);
+        assert_eq!(stale_after_secs(1800), 5400);
+
+        assert!(!is_stale(0, 60, now), "never fetched");
+        assert!(!is_stale(now - 10 * 60, 60, now));
+        assert!(is_stale(now - 16 * 60, 60, now));
+        assert!(!is_stale(now - 60 * 60, 1800, now));
+        assert!(is_stale(now - 2 * 60 * 60, 1800, now));
+
+        assert_eq!(format_age(40 * 60), "40 minutes");
+        assert_eq!(format_age(90 * 60), "1 hour");
+        assert_eq!(format_age(5 * 3600), "5 hours");
+        assert_eq!(format_age(3 * 86400), "3 days");
+    }
+
+    #[tokio::test]
+    async fn refresh_falls_back_to_cache_when_source_fails() {
+        let temp = TempStore::new("fallback");
+        temp.store.set_usage(50, 300, fetched_at()).unwrap();
+        let source = FixtureUsageSource::new(vec![
+            Err("Extraction timed out".to_string()),
+            Ok(ExtractionResult {
+                customer_id: Some(1),
+                usage_data: None,
+                usage_history: None,
+                usage_models: None,
+                error: Some("Not signed in".to_string()),
+                schema_drift: None,
+                extractor_version: None,
+                billing_entities: None,
+                session_expired: false,
+            }),
+        ]);
+        let manager = manager();
+
+        for _ in 0..2 {
+            let outcome = manager.refresh(&temp.store, &source).await.unwrap();
+            assert!(!outcome.live);
+            assert_eq!(outcome.summary.used, 50);
+            assert_eq!(outcome.summary.limit, 300);
+            assert_eq!(outcome.summary.timestamp, fetched_at());
         }
+        // The cached usage keeps its fetch time, an hour before the injected clock
+        let now = clock_at(2026, 3, 10, 12).now().timestamp();
+        assert!(is_stale(temp.store.get_last_fetch_timestamp(), 60, now));
+        assert!(!is_stale(temp.store.get_last_fetch_timestamp(), 1800, now));
+        assert_eq!(temp.store.get_customer_id(), None);
+        assert!(temp.store.get_usage_snapshots(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn refresh_without_usage_keeps_history() {
+        let temp = TempStore::new("history-only");
+        let mut result: ExtractionResult = serde_json::from_str(FIXTURE).unwrap();
+        result.usage_data = None;
+        let source = FixtureUsageSource::new(vec![Ok(result)]);
+
+        let outcome = manager().refresh(&temp.store, &source).await.unwrap();
+
+        assert!(!outcome.live);
+        assert_eq!(temp.store.get_usage_history().len(), 2);
+        assert!(temp.store.get_usage_snapshots(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_previous_cycle_stores_its_totals() {
+        let temp = TempStore::new("previous-cycle");
+        temp.store.set_usage(120, 300, fetched_at()).unwrap();
+        let source = FixtureUsageSource::from_json(PREVIOUS_CYCLE_FIXTURE).unwrap();
+        let query = UsageQuery::new(UsagePeriod::PreviousCycle, UsageGrouping::Day);
+
+        let period = manager().fetch_period(&temp.store, &source, &query).await.unwrap();
+
+        assert_eq!(period.start_date, "2026-02-01");
+        assert_eq!(period.end_date, "2026-03-01");
+        assert_eq!(period.days.len(), 2);
+        assert_eq!(period.used, 250);
+        assert_eq!(period.limit, 300);
+        assert_eq!(period.currency.as_deref(), Some("USD"));
+        let models: Vec<(&str, u32)> = period
+            .models
+            .iter()
+            .map(|m| (m.name.as_str(), m.included_requests))
+            .collect();
+        assert_eq!(models, [("o3", 25), ("GPT-4.1", 20)]);
+
+        // Live usage is untouched, the previous cycle is stored for comparison
+        assert_eq!(temp.store.get_usage(), (120, 300));
+        assert!(temp.store.get_usage_snapshots(None).is_empty());
+        assert_eq!(temp.store.get_usage_history().len(), 2);
+        let comparison = manager().cycle_comparison(&temp.store);
+        assert_eq!(comparison.current.cycle_key, "2026-03");
+        assert_eq!(comparison.current.used, 120);
+        let previous = comparison.previous.unwrap();
+        assert_eq!(previous.cycle_key, "2026-02");
+        assert_eq!(previous.used, 250);
+        assert_eq!(previous.billed_amount, 2.5);
+    }
+
+    #[tokio::test]
+    async fn fetch_previous_cycle_uses_calendar_months() {
+        let temp = TempStore::new("previous-month");
+        temp.store
+            .update_settings(|s| {
+                s.billing_cycle_anchor_day = 15;
+                s.billing_time_zone = "+05:00".to_string();
+            })
+            .unwrap();
+        let json = PREVIOUS_CYCLE_FIXTURE.replace("2026-02-27", "2026-02-03");
+        let source = FixtureUsageSource::from_json(&json).unwrap();
+        let query = UsageQuery::new(UsagePeriod::PreviousCycle, UsageGrouping::Day);
+
+        let period = manager().fetch_period(&temp.store, &source, &query).await.unwrap();
+
+        // "Last month" is February whatever the anchor day, so rows on both sides of the 15th count
+        assert_eq!(period.start_date, "2026-02-01");
+        assert_eq!(period.end_date, "2026-03-01");
+        assert_eq!(period.days.len(), 2);
+        assert_eq!(period.used, 250);
+
+        let stored = temp.store.get_cycle_usage("2026-02").unwrap();
+        assert_eq!(stored.start_date, "2026-02-01");
+        assert_eq!(stored.end_date, "2026-03-01");
+        let comparison = manager().cycle_comparison(&temp.store);
+        assert_eq!(comparison.current.cycle_key, "2026-03");
+        assert_eq!(comparison.current.start_date, "2026-03-01");
+        assert_eq!(comparison.previous.unwrap().used, 250);
+    }
+
+    #[tokio::test]
+    async fn fetch_last_days_sums_rows_in_range() {
+        let temp = TempStore::new("last-days");
+        let source = FixtureUsageSource::from_json(PREVIOUS_CYCLE_FIXTURE).unwrap();
+        let query = UsageQuery::new(UsagePeriod::LastDays { days: 11 }, UsageGrouping::Day);
+
+        let period = manager().fetch_period(&temp.store, &source, &query).await.unwrap();
+
+        // 28 Feb - 10 Mar: the card is ignored and the rows are summed
+        assert_eq!(period.start_date, "2026-02-28");
+        assert_eq!(period.days.len(), 2);
+        assert_eq!(period.used, 20);
+        assert_eq!(period.limit, 0);
+        assert!(manager().cycle_comparison(&temp.store).previous.is_none());
+    }
+
+    #[tokio::test]
+    async fn fetch_period_grouped_by_model() {
+        let temp = TempStore::new("by-model");
+        let mut result: ExtractionResult = serde_json::from_str(PREVIOUS_CYCLE_FIXTURE).unwrap();
+        result.usage_data = None;
+        result.usage_history = None;
+        result.usage_models = Some(vec![UsageModelRow {
+            name: "GPT-4.1".to_string(),
+            included_requests: 40,
+            billed_requests: 3,
+            gross_amount: 1.72,
+            billed_amount: 0.12,
+        }]);
+        let source = FixtureUsageSource::new(vec![Ok(result)]);
+        let query = UsageQuery::new(UsagePeriod::CurrentCycle, UsageGrouping::Model);
+
+        let period = manager().fetch_period(&temp.store, &source, &query).await.unwrap();
+
+        assert!(period.days.is_empty());
+        assert_eq!(period.models.len(), 1);
+        assert_eq!(period.used, 43);
+        assert_eq!(period.billed_amount, 0.12);
+        assert!(temp.store.get_usage_history().is_empty());
+    }
+
+    #[tokio::test]
+    async fn expired_session_pauses_polling_until_sign_in() {
+        let temp = TempStore::new("session-expired");
+        temp.store.set_customer_id(4242).unwrap();
+        temp.store.set_usage(50, 300, fetched_at()).unwrap();
+        let expired = || ExtractionResult {
+            customer_id: None,
+            usage_data: None,
+            usage_history: None,
+            usage_models: None,
+            error: Some("GitHub session expired".to_string()),
+            schema_drift: None,
+            extractor_version: None,
+            billing_entities: None,
+            session_expired: true,
+        };
+        let source = FixtureUsageSource::new(vec![Ok(expired()), Ok(expired())]);
+        let manager = manager();
+
+        let outcome = manager.poll(&temp.store, &source).await.unwrap();
+        assert!(outcome.session_expired);
+        assert!(!outcome.live);
+        assert_eq!(outcome.summary.used, 50);
+        assert!(temp.store.is_session_expired());
+
+        assert!(manager.poll(&temp.store, &source).await.is_none());
+        let outcome = manager.refresh(&temp.store, &source).await.unwrap();
+        assert!(!outcome.session_expired, "expiry is reported once");
+
+        temp.store.set_customer_id(4242).unwrap();
+        assert!(!temp.store.is_session_expired());
+    }
+
+    #[tokio::test]
+    async fn poll_skips_when_signed_out() {
+        let temp = TempStore::new("signed-out");
+        let source = FixtureUsageSource::from_json(FIXTURE).unwrap();
+
+        assert!(manager().poll(&temp.store, &source).await.is_none());
+        assert_eq!(source.remaining(), 1);
 
-        let days_until_limit = (remaining / daily_average).ceil() as i64;
-        Ok(Some(days_until_limit))
+        temp.store.set_customer_id(4242).unwrap();
+        let outcome = manager().poll(&temp.store, &source).await.unwrap();
+        assert_eq!(outcome.summary.used, 120);
     }
 }
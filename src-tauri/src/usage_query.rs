@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::billing::BillingCycle;
+
+/// `period` values of the billing usage endpoints, as sent by the time frame picker of the
+/// billing usage page: 3 is "Current month", 4 is "Last month" (UTC calendar months)
+const PERIOD_CURRENT_MONTH: u32 = 3;
+const PERIOD_PREVIOUS_MONTH: u32 = 4;
+
+/// `group` values of the billing usage endpoints
+const GROUP_BY_DAY: u32 = 0;
+const GROUP_BY_MODEL: u32 = 1;
+
+/// Longest "last N days" range; it may reach back at most into the previous month
+pub const MAX_LAST_DAYS: u32 = 62;
+
+/// Time range of a usage query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+/// The cycles are the calendar months the billing usage endpoints report, not the
+/// anchor-day cycles of the settings
+pub enum UsagePeriod {
+    CurrentCycle,
+    PreviousCycle,
+    /// The last `days` days, including today
+    LastDays { days: u32 },
+}
+
+/// How rows of the usage table are broken down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UsageGrouping {
+    Day,
+    Model,
+}
+
+/// What to ask the billing usage endpoints for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageQuery {
+    pub period: UsagePeriod,
+    pub group: UsageGrouping,
+}
+
+impl Default for UsageQuery {
+    fn default() -> Self {
+        Self {
+            period: UsagePeriod::CurrentCycle,
+            group: UsageGrouping::Day,
+        }
+    }
+}
+
+impl UsageQuery {
+    pub fn new(period: UsagePeriod, group: UsageGrouping) -> Self {
+        Self { period, group }
+    }
+
+    /// Dates covered by the query (UTC): start inclusive, end exclusive
+    pub fn date_range(&self, now: DateTime<Utc>) -> (NaiveDate, NaiveDate) {
+        let month = BillingCycle::calendar_month(now);
+        match self.period {
+            UsagePeriod::CurrentCycle => (month.start_date, month.end_date),
+            UsagePeriod::PreviousCycle => {
+                let previous = month.previous();
+                (previous.start_date, previous.end_date)
+            }
+            UsagePeriod::LastDays { days } => {
+                let days = days.clamp(1, MAX_LAST_DAYS) as i64;
+                let tomorrow = month.date_of(now) + Duration::days(1);
+                let start = (tomorrow - Duration::days(days)).max(month.previous().start_date);
+                (start, tomorrow)
+            }
+        }
+    }
+
+    /// `period` parameters to request, oldest first
+    /// A "last N days" range reaching before the current month needs the previous one too
+    pub fn period_params(&self, now: DateTime<Utc>) -> Vec<u32> {
+        match self.period {
+            UsagePeriod::CurrentCycle => vec![PERIOD_CURRENT_MONTH],
+            UsagePeriod::PreviousCycle => vec![PERIOD_PREVIOUS_MONTH],
+            UsagePeriod::LastDays { .. } => {
+                let (start, _) = self.date_range(now);
+                if start < BillingCycle::calendar_month(now).start_date {
+                    vec![PERIOD_PREVIOUS_MONTH, PERIOD_CURRENT_MONTH]
+                } else {
+                    vec![PERIOD_CURRENT_MONTH]
+                }
+            }
+        }
+    }
+
+    /// `group` parameter to request
+    pub fn group_param(&self) -> u32 {
+        match self.group {
+            UsageGrouping::Day => GROUP_BY_DAY,
+            UsageGrouping::Model => GROUP_BY_MODEL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn defaults_to_current_cycle_by_day() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let query = UsageQuery::default();
+
+        assert_eq!(query.period_params(now), [3]);
+        assert_eq!(query.group_param(), 0);
+    }
+
+    #[test]
+    fn previous_cycle_range() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 10, 0, 0, 0).unwrap();
+        let query = UsageQuery::new(UsagePeriod::PreviousCycle, UsageGrouping::Model);
+
+        assert_eq!(
+            query.date_range(now),
+            (date(2025, 12, 1), date(2026, 1, 1))
+        );
+        assert_eq!(query.period_params(now), [4]);
+        assert_eq!(query.group_param(), 1);
+    }
+
+    #[test]
+    fn last_days_within_the_cycle() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let query = UsageQuery::new(UsagePeriod::LastDays { days: 7 }, UsageGrouping::Day);
+
+        assert_eq!(
+            query.date_range(now),
+            (date(2026, 3, 4), date(2026, 3, 11))
+        );
+        assert_eq!(query.period_params(now), [3]);
+    }
+
+    #[test]
+    fn last_days_spanning_cycles() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 3, 12, 0, 0).unwrap();
+        let query = UsageQuery::new(UsagePeriod::LastDays { days: 7 }, UsageGrouping::Day);
+
+        assert_eq!(
+            query.date_range(now),
+            (date(2026, 2, 25), date(2026, 3, 4))
+        );
+        assert_eq!(query.period_params(now), [4, 3]);
+
+        // Never reaches past the previous month
+        let query = UsageQuery::new(UsagePeriod::LastDays { days: 365 }, UsageGrouping::Day);
+        assert_eq!(query.date_range(now).0, date(2026, 2, 1));
+    }
+
+    #[test]
+    fn deserializes_from_frontend_shape() {
+        let query: UsageQuery = serde_json::from_str(
+            r#"{ "period": { "kind": "lastDays", "days": 14 }, "group": "model" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            UsageQuery::new(UsagePeriod::LastDays { days: 14 }, UsageGrouping::Model)
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+
+use crate::accounts::DEFAULT_ACCOUNT_ID;
+use crate::auth::{AuthManager, ExtractionResult};
+use crate::billing_entity::BillingEntity;
+use crate::credentials::CredentialManager;
+use crate::rest_source::RestUsageSource;
+use crate::store::{AppSettings, StoreManager};
+use crate::usage_query::UsageQuery;
+
+/// Future returned by `UsageSource::fetch`
+pub type SourceFuture<'a> = Pin<Box<dyn Future<Output = Result<ExtractionResult, String>> + Send + 'a>>;
+
+/// Where fresh usage data comes from
+pub trait UsageSource: Send + Sync {
+    fn fetch(&self, query: &UsageQuery) -> SourceFuture<'_>;
+}
+
+/// Scrapes the GitHub billing page in a hidden webview
+pub struct WebviewUsageSource {
+    app: AppHandle,
+    account: String,
+    entity: BillingEntity,
+}
+
+impl WebviewUsageSource {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            account: DEFAULT_ACCOUNT_ID.to_string(),
+            entity: BillingEntity::personal(),
+        }
+    }
+
+    /// Use the webview session of account `id` instead of the default account's
+    pub fn with_account(mut self, id: &str) -> Self {
+        self.account = id.to_string();
+        self
+    }
+
+    /// Read the billing page of `entity` instead of the personal account
+    pub fn with_entity(mut self, entity: BillingEntity) -> Self {
+        self.entity = entity;
+        self
+    }
+}
+
+impl UsageSource for WebviewUsageSource {
+    fn fetch(&self, query: &UsageQuery) -> SourceFuture<'_> {
+        let query = *query;
+        Box::pin(async move {
+            let mut auth_manager = AuthManager::for_account(&self.account);
+            auth_manager.perform_query(&self.app, &self.entity, &query).await
+        })
+    }
+}
+
+/// Source selected by the `usageSource` setting, for the personal account
+/// Falls back to the webview while the REST source has no token
+pub fn configured_source(app: &AppHandle) -> Box<dyn UsageSource> {
+    configured_entity_source(app, &BillingEntity::personal())
+}
+
+/// Source selected by the `usageSource` setting, for `entity`
+pub fn configured_entity_source(app: &AppHandle, entity: &BillingEntity) -> Box<dyn UsageSource> {
+    let settings = app
+        .try_state::<StoreManager>()
+        .map(|store| store.get_settings())
+        .unwrap_or_else(AppSettings::default);
+
+    if settings.usage_source == "rest" {
+        let token = app
+            .try_state::<CredentialManager>()
+            .and_then(|credentials| credentials.github_token());
+        match token {
+            Some(token) => {
+                return Box::new(RestUsageSource::new(token, &settings).with_entity(entity.clone()))
+            }
+            None => log::warn!("REST usage source selected without a GitHub token, using the webview"),
+        }
+    }
+    Box::new(WebviewUsageSource::new(app.clone()).with_entity(entity.clone()))
+}
+
+/// Replays canned extraction results in order, for tests and offline runs
+/// The query is ignored; fails once every result has been consumed
+pub struct FixtureUsageSource {
+    results: Mutex<VecDeque<Result<ExtractionResult, String>>>,
+}
+
+impl FixtureUsageSource {
+    pub fn new(results: Vec<Result<ExtractionResult, String>>) -> Self {
+        Self {
+            results: Mutex::new(results.into()),
+        }
+    }
+
+    /// Source returning a single extraction result parsed from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let result: ExtractionResult = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse usage fixture: {}", e))?;
+        Ok(Self::new(vec![Ok(result)]))
+    }
+
+    /// Number of results not yet fetched
+    pub fn remaining(&self) -> usize {
+        self.results.lock().unwrap().len()
+    }
+}
+
+impl UsageSource for FixtureUsageSource {
+    fn fetch(&self, _query: &UsageQuery) -> SourceFuture<'_> {
+        let next = self.results.lock().unwrap().pop_front();
+        Box::pin(async move { next.unwrap_or_else(|| Err("No fixture results left".to_string())) })
+    }
+}
@@ -15,7 +15,7 @@ import { useAuth } from "@renderer/hooks/useAuth";
 import { Github, Loader2 } from "lucide-react";
 
 export function LoginPrompt() {
-  const { login, isLoading, hasError } = useAuth();
+  const { login, isLoading, hasError, sessionExpired } = useAuth();
 
   return (
     <div className="min-h-screen bg-background flex items-center justify-center p-4">
@@ -43,6 +43,15 @@ export function LoginPrompt() {
             predictions, and get notified before exceeding your quota.
           </p>
 
+          {sessionExpired && (
+            <div className="p-3 rounded-lg bg-muted border text-center">
+              <p className="text-sm text-muted-foreground">
+                Your GitHub session has expired. Sign in again to resume
+                tracking.
+              </p>
+            </div>
+          )}
+
           {hasError && (
             <div className="p-3 rounded-lg bg-destructive/10 border border-destructive/20 text-center">
               <p className="text-sm text-destructive">
@@ -60,10 +60,14 @@ export function useAuth() {
       },
     );
 
-    // Listen for session expiry
-    const unsubSessionExpired = window.electron.onSessionExpired?.(() => {
-      setAuthState("unauthenticated");
-    });
+    // Listen for session expiry; other accounts' sessions do not sign this view out
+    const unsubSessionExpired = window.electron.onSessionExpired?.(
+      (account: string) => {
+        if (account === "default") {
+          setAuthState("expired");
+        }
+      },
+    );
 
     // Listen for already authenticated notification
     const unsubAlreadyAuthenticated = window.electron.onAlreadyAuthenticated?.(
@@ -99,7 +103,8 @@ export function useAuth() {
   // Computed properties
   const isAuthenticated = authState === "authenticated";
   const isLoading = authState === "checking" || authState === "unknown";
-  const needsLogin = authState === "unauthenticated";
+  const sessionExpired = authState === "expired";
+  const needsLogin = authState === "unauthenticated" || sessionExpired;
   const hasError = authState === "error";
 
   return {
@@ -107,6 +112,7 @@ export function useAuth() {
     isAuthenticated,
     isLoading,
     needsLogin,
+    sessionExpired,
     hasError,
     login,
     logout,
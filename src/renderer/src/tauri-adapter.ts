@@ -40,6 +40,32 @@ interface RustAppSettings {
   showNotifications: boolean;
   notificationThresholds: number[];
   trayIconFormat: string;
+  snapshotRetentionDays: number;
+  snapshotCompactionAfterDays: number;
+  snapshotCompactionBucketMinutes: number;
+  billingCycleAnchorDay: number;
+  billingTimeZone: string;
+  holidays: string[];
+  usageSource: string;
+  premiumRequestLimit: number;
+  billingEntities: RustBillingEntity[];
+  selectedBillingEntities: string[];
+  accounts: RustAccountProfile[];
+  displayedAccount: string;
+  aggregateTrayText: boolean;
+}
+
+// Rust AccountProfile
+interface RustAccountProfile {
+  id: string;
+  name: string;
+}
+
+// Rust BillingEntity (personal account, organization or enterprise)
+interface RustBillingEntity {
+  kind: "personal" | "organization" | "enterprise";
+  slug: string;
+  name: string | null;
 }
 
 // Rust AuthState result
@@ -209,6 +235,7 @@ export async function initTauriAdapter() {
           billed_requests?: number;
           gross_amount?: number;
           billed_amount?: number;
+          currency?: string | null;
           models?: Array<{
             name: string;
             included_requests: number;
@@ -245,6 +272,7 @@ export async function initTauriAdapter() {
               billedRequests: entry.billed_requests ?? 0,
               grossAmount: entry.gross_amount ?? 0,
               billedAmount: entry.billed_amount ?? 0,
+              currency: entry.currency ?? undefined,
               models: entry.models?.map((m) => ({
                 name: m.name,
                 includedRequests: m.included_requests,
@@ -308,9 +336,9 @@ export async function initTauriAdapter() {
           if (idx !== -1) authListeners.splice(idx, 1);
         };
       },
-      onSessionExpired: (callback: () => void) => {
+      onSessionExpired: (callback: (account: string) => void) => {
         let unlisten: (() => void) | null = null;
-        listen("auth:session-expired", () => callback())
+        listen<string>("auth:session-expired", (event) => callback(event.payload))
           .then((stop) => {
             unlisten = stop;
           })
@@ -392,6 +420,7 @@ export async function initTauriAdapter() {
               billed_requests?: number;
               gross_amount?: number;
               billed_amount?: number;
+              currency?: string | null;
               models?: Array<{
                 name: string;
                 included_requests: number;
@@ -432,6 +461,7 @@ export async function initTauriAdapter() {
                 billedRequests: entry.billed_requests ?? 0,
                 grossAmount: entry.gross_amount ?? 0,
                 billedAmount: entry.billed_amount ?? 0,
+                currency: entry.currency ?? undefined,
                 models: entry.models?.map((m) => ({
                   name: m.name,
                   includedRequests: m.included_requests,
@@ -556,6 +586,20 @@ export async function initTauriAdapter() {
             lastFetchTimestamp: current.lastFetchTimestamp,
             isAuthenticated: current.isAuthenticated,
             updateChannel: current.updateChannel,
+            snapshotRetentionDays: current.snapshotRetentionDays,
+            snapshotCompactionAfterDays: current.snapshotCompactionAfterDays,
+            snapshotCompactionBucketMinutes:
+              current.snapshotCompactionBucketMinutes,
+            billingCycleAnchorDay: current.billingCycleAnchorDay,
+            billingTimeZone: current.billingTimeZone,
+            holidays: current.holidays,
+            usageSource: current.usageSource,
+            premiumRequestLimit: current.premiumRequestLimit,
+            billingEntities: current.billingEntities,
+            selectedBillingEntities: current.selectedBillingEntities,
+            accounts: current.accounts,
+            displayedAccount: current.displayedAccount,
+            aggregateTrayText: current.aggregateTrayText,
           };
 
           if (import.meta.env.DEV) {
@@ -12,6 +12,7 @@ export type AuthState =
   | "checking"
   | "authenticated"
   | "unauthenticated"
+  | "expired"
   | "error";
 
 // Customer ID result
@@ -56,7 +57,7 @@ export interface UpdateInfo {
 // IPC Events from main to renderer
 export interface MainToRendererEvents {
   "auth:state-changed": (state: AuthState) => void;
-  "auth:session-expired": () => void;
+  "auth:session-expired": (account: string) => void;
   "auth:ready": () => void;
   "auth:extraction-failed": (data: { error: string }) => void;
   "usage:data": (data: UsageFetchResult) => void;
@@ -88,7 +89,7 @@ export interface AppAPI {
   logout: () => void;
   checkAuth: () => void;
   onAuthStateChanged: (callback: (state: AuthState) => void) => () => void;
-  onSessionExpired: (callback: () => void) => () => void;
+  onSessionExpired: (callback: (account: string) => void) => () => void;
   onAlreadyAuthenticated: (callback: () => void) => () => void;
   onAuthExtractionFailed: (callback: (error: string) => void) => () => void;
 
@@ -25,6 +25,7 @@ export interface DailyUsage {
   billedRequests: number; // Add-on billed requests
   grossAmount: number; // Gross amount
   billedAmount: number; // Add-on cost
+  currency?: string; // ISO 4217 code of the amounts, when known
   models?: ModelUsage[]; // Breakdown by model
 }
 